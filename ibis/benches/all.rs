@@ -9,11 +9,13 @@ use criterion::{criterion_group, criterion_main, Criterion};
 mod checking_and_planning;
 mod checking_only;
 mod demo;
+mod types_and_tags;
 mod utils;
 
 use checking_and_planning::*;
 use checking_only::*;
 use demo::*;
+use types_and_tags::*;
 use utils::*;
 
 criterion_group!(
@@ -26,6 +28,7 @@ criterion_group!(
     criterion_benchmark_checking_only,
     criterion_benchmark_noop_planning,
     criterion_benchmark_solve_demo,
-    criterion_benchmark_solve_chromium_demo
+    criterion_benchmark_solve_chromium_demo,
+    criterion_benchmark_types_and_tags
 );
 criterion_main!(benches, micro_benches);
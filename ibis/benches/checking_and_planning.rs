@@ -5,10 +5,10 @@
 // https://developers.google.com/open-source/licenses/bsd
 
 use criterion::{black_box, Criterion};
-use ibis::best_solutions_to_json;
+use ibis::run_ibis;
 
 fn solve_demo(data: &str) {
-    let _result = best_solutions_to_json(data);
+    let _result = run_ibis(data);
     // TODO: use the result to ensure it is correct
 }
 
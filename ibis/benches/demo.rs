@@ -5,22 +5,22 @@
 // https://developers.google.com/open-source/licenses/bsd
 
 use criterion::{black_box, Criterion};
-use ibis::best_solutions_to_json;
+use ibis::run_ibis;
 
 fn solve_demo(data: &str) {
-    let _result = best_solutions_to_json(data);
+    let _result = run_ibis(data);
     // TODO: use the result to ensure it is correct
 }
 
 pub fn criterion_benchmark_solve_demo(c: &mut Criterion) {
-    let data = include_str!("../demo.json");
+    let data = include_str!("../examples/demo.json");
     c.bench_function("solve demo.json", |b| {
         b.iter(|| solve_demo(black_box(data)))
     });
 }
 
 pub fn criterion_benchmark_solve_chromium_demo(c: &mut Criterion) {
-    let data = include_str!("../chromium.json");
+    let data = include_str!("../examples/chromium.json");
     c.bench_function("solve chromium.json", |b| {
         b.iter(|| solve_demo(black_box(data)))
     });
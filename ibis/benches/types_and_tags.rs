@@ -0,0 +1,89 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use criterion::{black_box, Criterion};
+use ibis::run_ibis;
+
+fn solve_demo(data: &str) {
+    let _result = run_ibis(data);
+    // TODO: use the result to ensure it is correct
+}
+
+// Checks a recipe that uses optional fields, refinements, literals and a security lattice with
+// tags that are never claimed, to catch rules that consider every pair of types or tags.
+pub fn criterion_benchmark_types_and_tags(c: &mut Criterion) {
+    let data = r#"
+{
+  "flags": {
+    "planning": false
+  },
+  "capabilities": [
+    ["write", "read"],
+    ["any", "read"],
+    ["write", "any"]
+  ],
+  "subtypes": [
+    ["Int", "Number"],
+    ["Int", "Serializable"],
+    ["String", "Serializable"]
+  ],
+  "less_private_than": [
+    ["public", "internal"],
+    ["internal", "confidential"],
+    ["confidential", "secret"],
+    ["secret", "top_secret"],
+    ["location", "precise_location"],
+    ["contacts", "call_log"],
+    ["health", "diagnosis"],
+    ["diagnosis", "genome"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "write {name: String where len <= 64, age: Int where 0 <= x < 150}"],
+        ["p_b", "b", "read {name: String where len <= 256, age?: Int where x >= 0}"],
+        ["p_c", "c", "write {name: String, nickname?: String, size: \"small\" | \"large\"}"],
+        ["p_d", "d", "read {name: *, size?: String}"],
+        ["p_e", "e", "write Int where 0 <= x < 100"],
+        ["p_f", "f", "read Int where x < 1000"],
+        ["p_g", "g", "write 42"],
+        ["p_h", "h", "read Number"],
+        ["p_i", "i", "any {name: String, tags?: List(String)}"],
+        ["p_j", "j", "read {tags?: List(Serializable)}"]
+      ],
+      "claims": [
+        ["a", "confidential"],
+        ["a", "health"],
+        ["c", "location"],
+        ["e", "internal"],
+        ["g", "contacts"],
+        ["i", "secret"]
+      ],
+      "checks": [
+        ["b", "confidential"],
+        ["d", "public"],
+        ["f", "internal"],
+        ["h", "contacts"],
+        ["j", "secret"]
+      ],
+      "edges": [
+        ["a", "b"],
+        ["a", "d"],
+        ["a", "i"],
+        ["c", "d"],
+        ["c", "i"],
+        ["e", "f"],
+        ["e", "h"],
+        ["g", "f"],
+        ["g", "h"],
+        ["i", "j"]
+      ]
+    }
+  ]
+}
+"#;
+    c.bench_function("types_and_tags", |b| b.iter(|| solve_demo(black_box(data))));
+}
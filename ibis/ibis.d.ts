@@ -15,3 +15,4 @@ export function check_is_subtype(
   subtypes: [string, string][]
 ): boolean;
 export function run_ibis(input: string): string;
+export function infer_schema(samples: string): string;
@@ -8,6 +8,7 @@ import {
     default as ibis,
     version_info,
     run_ibis as run_ibis_impl,
+    infer_schema as infer_schema_impl,
 } from './pkg/ibis.js';
 
 let ibisStatusCallback = undefined;
@@ -83,6 +84,18 @@ export function run_ibis(input) {
     }
 }
 
+export function infer_schema(samples) {
+    try {
+        logStatus(`Inferring schema...`);
+        const result = infer_schema_impl(samples);
+        logStatus(`Done`);
+        return result;
+    } catch (err) {
+        logStatus(`${err}`, 'error');
+        throw err;
+    }
+}
+
 export function check_is_subtype(subtype, supertype, subtypes) {
     const input = {
         flags: { planning: false },
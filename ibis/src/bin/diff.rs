@@ -0,0 +1,30 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::run_ibis;
+use ibis::{Ibis, IbisError};
+
+fn run_file(path: &str) -> Ibis {
+    let data = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("IO Error, reading {}: {}", path, e));
+    run_ibis(&data)
+}
+
+fn main() -> Result<(), IbisError> {
+    eprintln!("{}", ibis::version_info());
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <before.json> <after.json>", args[0]);
+        std::process::exit(2);
+    }
+    eprintln!("Comparing solutions...");
+    let diff = Ibis::diff(&run_file(&args[1]), &run_file(&args[2]));
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&diff).expect("Couldn't serialize Ibis diff")
+    );
+    Ok(())
+}
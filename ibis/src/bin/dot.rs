@@ -23,14 +23,3 @@ fn main() -> Result<(), IbisError> {
     );
     Ok(())
 }
-
-/*
-#[test]
-fn demo_json_round_trips() {
-    let data = include_str!("../../demo.json");
-    let recipe: Recipe = serde_json::from_str(data).expect("JSON Error?");
-
-    let serialized = serde_json::to_string(&recipe).unwrap();
-    let deserialized: Recipe = serde_json::from_str(&serialized).unwrap();
-
-}*/
@@ -0,0 +1,20 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::infer_schema;
+use ibis::IbisError;
+use std::io::Read;
+
+fn main() -> Result<(), IbisError> {
+    eprintln!("{}", ibis::version_info());
+    let mut data = String::new();
+    std::io::stdin()
+        .read_to_string(&mut data)
+        .expect("IO Error, reading stdin");
+    eprintln!("Inferring schema...");
+    println!("{}", infer_schema(&data));
+    Ok(())
+}
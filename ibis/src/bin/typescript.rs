@@ -0,0 +1,26 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::import_typescript;
+use ibis::IbisError;
+use std::io::Read;
+
+fn main() -> Result<(), IbisError> {
+    eprintln!("{}", ibis::version_info());
+    let mut data = String::new();
+    std::io::stdin()
+        .read_to_string(&mut data)
+        .expect("IO Error, reading stdin");
+    eprintln!("Importing TypeScript declarations...");
+    let imported = import_typescript(&data);
+    for warning in &imported.warnings {
+        eprintln!("{}", warning);
+    }
+    let config = serde_json::to_string_pretty(&imported.to_config())
+        .expect("Couldn't serialize imported config");
+    println!("{}", config);
+    Ok(())
+}
@@ -18,7 +18,6 @@ use std::{cell::RefCell, sync::Arc};
 
 pub struct Ctx {
     pub last_id: EntityIdBackingType,
-    pub solution_id: SolutionIdBackingType,
     // TODO: Consider using https://docs.rs/bimap/latest/bimap/
     pub id_to_type: BiMap<Ent, Arc<Type>>,
     pub id_to_solution: BiMap<Sol, Arc<SolutionData>>,
@@ -32,7 +31,6 @@ impl Ctx {
     fn new() -> Self {
         Self {
             last_id: 0,
-            solution_id: 0, // zero is never used except for the 'empty' solution
             id_to_type: BiMap::new(),
             id_to_solution: BiMap::new(),
             #[cfg(feature = "ancestors")]
@@ -0,0 +1,101 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::recipes::{Config, Ibis, Leak, Node, Recipe};
+use crate::util::sort_by_name;
+use crate::{Ent, Sol};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// A minimal set of a recipe's edges (and the particles they connect) that still leaks, i.e. no
+// edge can be removed from it, though a smaller set may exist.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Counterexample {
+    pub leak: Leak,
+    pub edges: Vec<(Ent, Ent)>,
+    pub particles: Vec<Ent>,
+}
+
+fn same_leak(a: &Leak, b: &Leak) -> bool {
+    let Leak(_, node, expected, source, tag) = a;
+    let Leak(_, other_node, other_expected, other_source, other_tag) = b;
+    (node, expected, source, tag) == (other_node, other_expected, other_source, other_tag)
+}
+
+impl Ibis {
+    // Checks a solution with the given configuration, and this Ibis' recipe data.
+    pub(crate) fn check_sol(&self, config: &Config, sol: Sol) -> Recipe {
+        let checker = Ibis {
+            config: Config {
+                flags: BTreeMap::new(), // Only check the edges, without planning or output.
+                ..config.clone()
+            },
+            shared: Recipe {
+                edges: vec![],
+                ..self.shared.clone()
+            },
+            recipes: vec![Recipe::from_sol(sol)],
+            ..Ibis::default()
+        };
+        let mut checked = checker.extract_solutions_with_loss(None);
+        checked.recipes.pop().expect("Should check a single recipe")
+    }
+
+    fn particles_of(&self, edges: &BTreeSet<(Ent, Ent)>) -> Vec<Ent> {
+        let nodes: BTreeSet<Ent> = edges.iter().flat_map(|(from, to)| [*from, *to]).collect();
+        let particles: BTreeSet<Ent> = self
+            .shared
+            .nodes
+            .iter()
+            .filter(|Node(_, node, _)| nodes.contains(node))
+            .map(|Node(particle, _, _)| *particle)
+            .collect();
+        let mut particles: Vec<Ent> = particles.into_iter().collect();
+        sort_by_name(&mut particles);
+        particles
+    }
+
+    // Finds a minimal subset of the recipe's edges for each of its leaks, such that removing any
+    // other edge from the subset would prevent the leak. This requires the recipe's nodes, claims
+    // and checks in 'shared' (i.e. an Ibis returned by extract_solutions).
+    // Only leaks from checks are explained: category leaks, implicit flows, integrity violations
+    // and type errors have no counterexamples.
+    pub fn counterexamples(&self, recipe: &Recipe) -> Vec<Counterexample> {
+        let mut leaks: Vec<&Leak> = vec![];
+        for leak in &recipe.feedback.leaks {
+            if !leaks.iter().any(|other| same_leak(leak, other)) {
+                leaks.push(leak);
+            }
+        }
+        leaks
+            .into_iter()
+            .map(|leak| {
+                let mut sol = Sol::from(recipe);
+                for (from, to) in &recipe.edges {
+                    let without_edge = sol.remove_edge(*from, *to);
+                    let checked = self.check_sol(&self.config, without_edge);
+                    if checked
+                        .feedback
+                        .leaks
+                        .iter()
+                        .any(|other| same_leak(leak, other))
+                    {
+                        sol = without_edge; // The edge is not needed to reproduce the leak.
+                    }
+                }
+                let edges = &sol.solution().edges;
+                let particles = self.particles_of(edges);
+                let mut edges: Vec<(Ent, Ent)> = edges.iter().cloned().collect();
+                sort_by_name(&mut edges);
+                Counterexample {
+                    leak: *leak,
+                    particles,
+                    edges,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::recipes::{
+    CategoryLeak, Ibis, ImplicitFlow, IntegrityViolation, Leak, Recipe, TypeError,
+};
+use crate::Ent;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Solutions are matched by their edges (which their ids are derived from).
+type Edges = Vec<(Ent, Ent)>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct LeakChange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<Ent>,
+    pub node: Ent,
+    pub expected: Ent,
+    pub source: Ent,
+    pub tag: Ent,
+}
+
+// The changes to the feedback of a solution found both before and after.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SolutionDiff {
+    pub edges: Edges,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_leaks: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_leaks: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_type_errors: Edges,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_type_errors: Edges,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_category_leaks: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_category_leaks: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_implicit_flows: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_implicit_flows: Vec<LeakChange>,
+    // For integrity violations, 'expected' is the required tag.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_integrity_violations: Vec<LeakChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_integrity_violations: Vec<LeakChange>,
+}
+
+impl SolutionDiff {
+    pub fn has_changes(&self) -> bool {
+        self != &SolutionDiff {
+            edges: self.edges.clone(),
+            ..SolutionDiff::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct IbisDiff {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_solutions: Vec<Edges>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lost_solutions: Vec<Edges>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_solutions: Vec<SolutionDiff>,
+}
+
+impl IbisDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &IbisDiff::default()
+    }
+}
+
+fn by_name<T: Ord + Clone>(items: &BTreeSet<T>, name: impl Fn(&T) -> String) -> Vec<T> {
+    let mut items: Vec<T> = items.iter().cloned().collect();
+    items.sort_by_cached_key(name);
+    items
+}
+
+fn edge_name((from, to): &(Ent, Ent)) -> String {
+    format!("{} -> {}", from, to)
+}
+
+fn edges_name(edges: &Edges) -> String {
+    edges.iter().map(edge_name).collect::<Vec<_>>().join(", ")
+}
+
+fn leak_name(leak: &LeakChange) -> String {
+    format!(
+        "{} {} {} {} {}",
+        leak.category
+            .map(|category| category.to_string())
+            .unwrap_or_default(),
+        leak.node,
+        leak.expected,
+        leak.source,
+        leak.tag
+    )
+}
+
+fn leak_change(
+    category: Option<Ent>,
+    node: Ent,
+    expected: Ent,
+    source: Ent,
+    tag: Ent,
+) -> LeakChange {
+    LeakChange {
+        category,
+        node,
+        expected,
+        source,
+        tag,
+    }
+}
+
+fn edges_of(recipe: &Recipe) -> Edges {
+    let edges: BTreeSet<(Ent, Ent)> = recipe.edges.iter().cloned().collect();
+    by_name(&edges, edge_name)
+}
+
+fn leaks_of(recipe: &Recipe) -> BTreeSet<LeakChange> {
+    recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, expected, source, tag)| {
+            leak_change(None, *node, *expected, *source, *tag)
+        })
+        .collect()
+}
+
+fn category_leaks_of(recipe: &Recipe) -> BTreeSet<LeakChange> {
+    recipe
+        .feedback
+        .category_leaks
+        .iter()
+        .map(|CategoryLeak(_, category, node, expected, source, tag)| {
+            leak_change(Some(*category), *node, *expected, *source, *tag)
+        })
+        .collect()
+}
+
+fn implicit_flows_of(recipe: &Recipe) -> BTreeSet<LeakChange> {
+    recipe
+        .feedback
+        .implicit_flows
+        .iter()
+        .map(|ImplicitFlow(_, node, expected, source, tag)| {
+            leak_change(None, *node, *expected, *source, *tag)
+        })
+        .collect()
+}
+
+fn integrity_violations_of(recipe: &Recipe) -> BTreeSet<LeakChange> {
+    recipe
+        .feedback
+        .integrity_violations
+        .iter()
+        .map(|IntegrityViolation(_, node, required, source, tag)| {
+            leak_change(None, *node, *required, *source, *tag)
+        })
+        .collect()
+}
+
+fn type_errors_of(recipe: &Recipe) -> BTreeSet<(Ent, Ent)> {
+    recipe
+        .feedback
+        .type_errors
+        .iter()
+        .map(|TypeError(_, from, _, to, _)| (*from, *to))
+        .collect()
+}
+
+// The items only found after, and the items only found before.
+fn changes<T: Ord + Clone>(
+    before: &BTreeSet<T>,
+    after: &BTreeSet<T>,
+    name: impl Fn(&T) -> String + Copy,
+) -> (Vec<T>, Vec<T>) {
+    (
+        by_name(&after.difference(before).cloned().collect(), name),
+        by_name(&before.difference(after).cloned().collect(), name),
+    )
+}
+
+fn solution_diff(edges: Edges, before: &Recipe, after: &Recipe) -> SolutionDiff {
+    let leak_changes =
+        |of: fn(&Recipe) -> BTreeSet<LeakChange>| changes(&of(before), &of(after), leak_name);
+    let (new_leaks, fixed_leaks) = leak_changes(leaks_of);
+    let (new_type_errors, fixed_type_errors) =
+        changes(&type_errors_of(before), &type_errors_of(after), edge_name);
+    let (new_category_leaks, fixed_category_leaks) = leak_changes(category_leaks_of);
+    let (new_implicit_flows, fixed_implicit_flows) = leak_changes(implicit_flows_of);
+    let (new_integrity_violations, fixed_integrity_violations) =
+        leak_changes(integrity_violations_of);
+    SolutionDiff {
+        edges,
+        new_leaks,
+        fixed_leaks,
+        new_type_errors,
+        fixed_type_errors,
+        new_category_leaks,
+        fixed_category_leaks,
+        new_implicit_flows,
+        fixed_implicit_flows,
+        new_integrity_violations,
+        fixed_integrity_violations,
+    }
+}
+
+impl Ibis {
+    // Compares the solutions (and their feedback) of two versions of the same recipes.
+    pub fn diff(before: &Ibis, after: &Ibis) -> IbisDiff {
+        let solutions = |ibis: &Ibis| -> BTreeMap<String, (Edges, Recipe)> {
+            ibis.recipes
+                .iter()
+                .map(|recipe| {
+                    let edges = edges_of(recipe);
+                    (edges_name(&edges), (edges, recipe.clone()))
+                })
+                .collect()
+        };
+        let (before, after) = (solutions(before), solutions(after));
+        let mut diff = IbisDiff::default();
+        for (name, (edges, recipe)) in &after {
+            match before.get(name) {
+                None => diff.new_solutions.push(edges.clone()),
+                Some((_, before_recipe)) => {
+                    let changes = solution_diff(edges.clone(), before_recipe, recipe);
+                    if changes.has_changes() {
+                        diff.changed_solutions.push(changes);
+                    }
+                }
+            }
+        }
+        for (name, (edges, _)) in &before {
+            if !after.contains_key(name) {
+                diff.lost_solutions.push(edges.clone());
+            }
+        }
+        diff
+    }
+}
@@ -4,7 +4,10 @@
 // license that can be found in the LICENSE file or at
 // https://developers.google.com/open-source/licenses/bsd
 
-use crate::{type_parser_cache::read_type, type_struct::Type};
+use crate::{
+    type_parser_cache::{read_capability, read_type},
+    type_struct::{normalize, Type, UNIVERSAL},
+};
 
 use super::context::{Ctx, CTX};
 use serde::{Deserialize, Serialize};
@@ -31,6 +34,14 @@ impl From<String> for Ent {
     }
 }
 
+// Reads a declared capability, which (unlike a type) may be namespaced with ':', e.g. fs:write.
+pub fn deserialize_capability<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Ent, D::Error> {
+    let cap = String::deserialize(deserializer)?;
+    Ok(Ent::by_type(read_capability(&cap)))
+}
+
 impl Ent {
     fn new(ctx: &mut Ctx, ty: Arc<Type>) -> Self {
         let id = ctx.last_id;
@@ -63,6 +74,61 @@ impl Ent {
             .collect()
     }
 
+    // Whether this (parameterised) capability matches a pattern, e.g. write(append) matches
+    // write(*).
+    pub fn matches_capability_pattern(&self, pattern: Ent) -> bool {
+        let ty = self.get_type();
+        let pattern = pattern.get_type();
+        !pattern.args.is_empty()
+            && ty.name == pattern.name
+            && ty.args.len() == pattern.args.len()
+            && ty
+                .args
+                .iter()
+                .zip(pattern.args.iter())
+                .all(|(arg, pattern_arg)| pattern_arg.name == UNIVERSAL || arg == pattern_arg)
+    }
+
+    pub fn unrefined(&self) -> Option<Ent> {
+        self.get_type().unrefined().map(Ent::by_type)
+    }
+
+    // Whether self is a refinement that implies the refinement on other.
+    pub fn refinement_implies(&self, other: Ent) -> bool {
+        match (&self.get_type().refinement, &other.get_type().refinement) {
+            (Some(refinement), Some(other_refinement)) => refinement.implies(other_refinement),
+            _ => false,
+        }
+    }
+
+    // Whether self is a literal that satisfies the refinement on other.
+    pub fn satisfies_refinement_of(&self, other: Ent) -> bool {
+        match &other.get_type().refinement {
+            Some(refinement) => refinement.accepts_literal(&self.get_type()),
+            None => false,
+        }
+    }
+
+    // The node and label referred to by a field identifier (e.g. person.ssn), if this is one.
+    pub fn field_of_node(&self) -> Option<(Ent, Ent)> {
+        let ty = self.get_type();
+        if !ty.args.is_empty() {
+            return None;
+        }
+        let (node, label) = ty.name.rsplit_once('.')?;
+        if node.is_empty() || label.is_empty() {
+            return None;
+        }
+        Some((
+            Ent::by_type(Type::new(node)),
+            Ent::by_type(Type::new(label)),
+        ))
+    }
+
+    pub fn literal_base(&self) -> Option<&'static str> {
+        self.get_type().literal_base()
+    }
+
     pub fn num_args(&self) -> usize {
         self.get_type().args.len()
     }
@@ -72,9 +138,9 @@ impl Ent {
     }
 
     pub fn by_type<T: Into<Arc<Type>>>(ty: T) -> Ent {
+        let ty = normalize(ty.into());
         let guard = CTX.lock().expect("Shouldn't fail");
         let mut ctx = (*guard).borrow_mut();
-        let ty = ty.into();
         Ent::get_by_type(&mut ctx, &ty).unwrap_or_else(|| Ent::new(&mut ctx, ty))
     }
 }
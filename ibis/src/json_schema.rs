@@ -0,0 +1,397 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::recipes::SubtypeInput;
+use crate::refinement::{Bounds, Comparison, Refinement, Subject};
+use crate::schema_inference::{label_of, LIST, NULL, NUMBER};
+use crate::type_parser_cache::read_type;
+use crate::type_struct::*;
+use crate::Ent;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+// Capabilities have no JSON Schema equivalent, so they are kept as an extension keyword.
+const CAPABILITY_KEYWORD: &str = "x-ibis-capability";
+
+// The result of importing a JSON Schema document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonSchemaImport {
+    // The type described by the root of the document.
+    pub ty: Ent,
+    // Subtyping relations defining each of the document's named definitions ($defs).
+    pub subtypes: Vec<SubtypeInput>,
+    // Parts of the document that could not be imported.
+    pub warnings: Vec<String>,
+}
+
+fn ent(ty: Type) -> Ent {
+    Ent::by_type(Arc::new(ty))
+}
+
+fn union_of(mut members: Vec<Type>) -> Type {
+    if members.len() == 1 {
+        members.pop().expect("Members should have a single element")
+    } else {
+        Type::new(UNION).with_args(members)
+    }
+}
+
+fn literal(value: &Value) -> Type {
+    match value {
+        Value::Null => Type::new(NULL),
+        Value::Bool(value) => Type::new(&value.to_string()),
+        Value::Number(value) if value.is_i64() => Type::new(&value.to_string()),
+        // Quotes (and backslashes) in the string are escaped as in JSON, e.g. "say \"hi\"".
+        Value::String(_) => Type::new(&value.to_string()),
+        _ => Type::new(UNIVERSAL), // There are no literal types for floats, lists or objects.
+    }
+}
+
+// Ibis has no intersection types, so the members of an 'allOf' are merged into the schema. Their
+// properties and patterns are combined; otherwise the first schema to use a keyword decides it.
+fn merge_all_of(schema: &Map<String, Value>) -> Map<String, Value> {
+    let mut merged = schema.clone();
+    let members = match merged.remove("allOf") {
+        Some(Value::Array(members)) => members,
+        _ => return merged,
+    };
+    for member in members.iter().filter_map(Value::as_object) {
+        for (keyword, value) in merge_all_of(member) {
+            match (keyword.as_str(), merged.get_mut(&keyword), value) {
+                ("properties", Some(Value::Object(properties)), Value::Object(more)) => {
+                    properties.extend(more)
+                }
+                ("required" | "pattern", Some(Value::Array(values)), Value::Array(more)) => {
+                    values.extend(more)
+                }
+                ("required" | "pattern", Some(existing), value) => {
+                    let mut values = vec![existing.take()];
+                    match value {
+                        Value::Array(more) => values.extend(more),
+                        value => values.push(value),
+                    }
+                    *existing = Value::Array(values);
+                }
+                (_, None, value) => {
+                    merged.insert(keyword, value);
+                }
+                _ => {}
+            }
+        }
+    }
+    merged
+}
+
+struct Importer<'a> {
+    document: &'a Value,
+    subtypes: Vec<SubtypeInput>,
+    warnings: Vec<String>,
+    names: BTreeMap<String, String>, // reference, name of its type
+}
+
+impl Importer<'_> {
+    // The name of a definition's type, e.g. 'Person' for '#/$defs/Person', unless another
+    // definition already has that name (e.g. '#/definitions/Person').
+    fn definition_name(&self, reference: &str) -> String {
+        let path = reference.trim_start_matches('#').trim_start_matches('/');
+        let short = ["$defs/", "definitions/"]
+            .iter()
+            .find_map(|container| path.strip_prefix(container))
+            .unwrap_or(path);
+        for name in [short, path, reference] {
+            if !name.is_empty() && !self.names.values().any(|other| other == name) {
+                return name.to_string();
+            }
+        }
+        reference.to_string()
+    }
+
+    // Imports a named definition (e.g. '#/$defs/Person'), the first time it is referenced.
+    fn definition(&mut self, reference: &str) -> Type {
+        if let Some(name) = self.names.get(reference) {
+            return Type::new(name);
+        }
+        let schema = match reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.document.pointer(pointer))
+        {
+            Some(schema) => schema,
+            None => {
+                self.warnings
+                    .push(format!("Unknown JSON Schema reference '{}'", reference));
+                return Type::new(UNIVERSAL);
+            }
+        };
+        let name = self.definition_name(reference);
+        self.names.insert(reference.to_string(), name.clone());
+        let body = ent(self.import(schema));
+        let named = ent(Type::new(&name));
+        self.subtypes.push(SubtypeInput(named, body));
+        self.subtypes.push(SubtypeInput(body, named));
+        Type::new(&name)
+    }
+
+    fn refinement(&self, schema: &Map<String, Value>) -> Option<Refinement> {
+        let mut refinement = Refinement::default();
+        let int = |keyword: &str| schema.get(keyword).and_then(Value::as_i64);
+        let constraints = [
+            ("minimum", Subject::Value, Comparison::GreaterOrEqual),
+            ("maximum", Subject::Value, Comparison::LessOrEqual),
+            ("exclusiveMinimum", Subject::Value, Comparison::Greater),
+            ("exclusiveMaximum", Subject::Value, Comparison::Less),
+            ("minLength", Subject::Length, Comparison::GreaterOrEqual),
+            ("maxLength", Subject::Length, Comparison::LessOrEqual),
+        ];
+        for (keyword, subject, comparison) in constraints {
+            if let Some(value) = int(keyword) {
+                refinement.constrain(subject, comparison, value);
+            }
+        }
+        match schema.get("pattern") {
+            Some(Value::String(pattern)) => refinement.add_pattern(pattern),
+            Some(Value::Array(patterns)) => {
+                // Merged from an 'allOf'.
+                for pattern in patterns.iter().filter_map(Value::as_str) {
+                    refinement.add_pattern(pattern);
+                }
+            }
+            _ => {}
+        }
+        if refinement == Refinement::default() {
+            None
+        } else {
+            Some(refinement)
+        }
+    }
+
+    fn object(&mut self, schema: &Map<String, Value>) -> Type {
+        let required: BTreeSet<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let mut fields: Vec<Type> = vec![];
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (label, property) in properties {
+                if property == &Value::Bool(false) {
+                    continue; // The property is not allowed.
+                }
+                let labelled = if required.contains(label.as_str()) {
+                    LABELLED
+                } else {
+                    OPTIONAL_LABELLED
+                };
+                fields.push(
+                    Type::new(labelled)
+                        .with_arg(Type::new(&label_of(label)))
+                        .with_arg(self.import(property)),
+                );
+            }
+        }
+        if fields.len() == 1 {
+            fields.pop().expect("Fields should have a single element")
+        } else {
+            Type::new(PRODUCT).with_args(fields)
+        }
+    }
+
+    fn primitive(&mut self, name: &str, schema: &Map<String, Value>) -> Type {
+        let ty = match name {
+            "string" => Type::new(STRING),
+            "integer" => Type::new(INT),
+            "number" => Type::new(NUMBER),
+            "boolean" => return Type::new(BOOLEAN),
+            "null" => return Type::new(NULL),
+            "array" => {
+                let items = schema
+                    .get("items")
+                    .map_or_else(|| Type::new(UNIVERSAL), |items| self.import(items));
+                return Type::new(LIST).with_arg(items);
+            }
+            "object" => return self.object(schema),
+            _ => return Type::new(UNIVERSAL),
+        };
+        match self.refinement(schema) {
+            Some(refinement) => ty.with_refinement(refinement),
+            None => ty,
+        }
+    }
+
+    fn import(&mut self, schema: &Value) -> Type {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => {
+                self.warnings
+                    .push("JSON Schema 'false' has no values, so is imported as '*'".to_string());
+                return Type::new(UNIVERSAL);
+            }
+            _ => return Type::new(UNIVERSAL),
+        };
+        let merged;
+        let schema = if schema.contains_key("allOf") {
+            merged = merge_all_of(schema);
+            &merged
+        } else {
+            schema
+        };
+        let ty = if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            self.definition(reference)
+        } else if let Some(value) = schema.get("const") {
+            literal(value)
+        } else if let Some(Value::Array(values)) = schema.get("enum") {
+            union_of(values.iter().map(literal).collect())
+        } else if let Some(Value::Array(members)) = schema.get("anyOf").or(schema.get("oneOf")) {
+            union_of(members.iter().map(|member| self.import(member)).collect())
+        } else {
+            match schema.get("type") {
+                Some(Value::String(name)) => self.primitive(name, schema),
+                Some(Value::Array(names)) => union_of(
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|name| self.primitive(name, schema))
+                        .collect(),
+                ),
+                _ if schema.contains_key("properties") => self.object(schema),
+                _ => Type::new(UNIVERSAL),
+            }
+        };
+        match schema.get(CAPABILITY_KEYWORD).and_then(Value::as_str) {
+            Some(capability) => ty.with_capability_term(read_type(capability)),
+            None => ty,
+        }
+    }
+}
+
+// Imports a JSON Schema document as an Ibis type.
+pub fn import_json_schema(schema: &Value) -> JsonSchemaImport {
+    let mut importer = Importer {
+        document: schema,
+        subtypes: vec![],
+        warnings: vec![],
+        names: BTreeMap::new(),
+    };
+    let ty = ent(importer.import(schema));
+    JsonSchemaImport {
+        ty,
+        subtypes: importer.subtypes,
+        warnings: importer.warnings,
+    }
+}
+
+fn literal_value(ty: &Type) -> Option<Value> {
+    let base = ty.literal_base()?;
+    match serde_json::from_str(&ty.name) {
+        Ok(value) => Some(value),
+        Err(_) if base == STRING => Some(json!(ty.name[1..ty.name.len() - 1])),
+        Err(_) => None,
+    }
+}
+
+fn add_bounds(schema: &mut Map<String, Value>, bounds: &Bounds, min: &str, max: &str) {
+    if let Some(value) = bounds.min {
+        schema.insert(min.to_string(), json!(value));
+    }
+    if let Some(value) = bounds.max {
+        schema.insert(max.to_string(), json!(value));
+    }
+}
+
+fn refined_schema(name: &str, refinement: &Refinement) -> Value {
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!(name));
+    add_bounds(&mut schema, &refinement.value, "minimum", "maximum");
+    add_bounds(&mut schema, &refinement.length, "minLength", "maxLength");
+    let patterns: Vec<Value> = refinement
+        .patterns
+        .iter()
+        .map(|pattern| json!({ "pattern": pattern }))
+        .collect();
+    match patterns.len() {
+        0 => {}
+        1 => {
+            schema.insert("pattern".to_string(), patterns[0]["pattern"].clone());
+        }
+        _ => {
+            schema.insert("allOf".to_string(), Value::Array(patterns));
+        }
+    }
+    Value::Object(schema)
+}
+
+fn object_schema(fields: &[Arc<Type>]) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for field in fields {
+        let (label, ty) = match (field.args.first(), field.args.get(1)) {
+            (Some(label), Some(ty)) => (label.to_string(), ty),
+            _ => continue,
+        };
+        if field.name == LABELLED {
+            required.push(json!(label));
+        }
+        properties.insert(label, type_to_schema(ty));
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+fn type_to_schema(ty: &Type) -> Value {
+    if let Some(value) = literal_value(ty) {
+        return json!({ "const": value });
+    }
+    let primitive = match ty.name.as_str() {
+        STRING => Some("string"),
+        INT => Some("integer"),
+        NUMBER => Some("number"),
+        BOOLEAN => Some("boolean"),
+        NULL => Some("null"),
+        _ => None,
+    };
+    if let (Some(name), true) = (primitive, ty.args.is_empty()) {
+        return match &ty.refinement {
+            Some(refinement) => refined_schema(name, refinement),
+            None => json!({ "type": name }),
+        };
+    }
+    match ty.name.as_str() {
+        UNIVERSAL => json!({}),
+        WITH_CAPABILITY if ty.args.len() == 2 => {
+            let mut schema = type_to_schema(&ty.args[1]);
+            schema[CAPABILITY_KEYWORD] = json!(ty.args[0].to_string());
+            schema
+        }
+        LABELLED | OPTIONAL_LABELLED => object_schema(&[Arc::new(ty.clone())]),
+        PRODUCT => object_schema(&ty.args),
+        UNION if ty.args.is_empty() => json!(false),
+        UNION => {
+            let literals: Option<Vec<Value>> =
+                ty.args.iter().map(|arg| literal_value(arg)).collect();
+            match literals {
+                Some(values) => json!({ "enum": values }),
+                None => {
+                    let members: Vec<Value> =
+                        ty.args.iter().map(|arg| type_to_schema(arg)).collect();
+                    json!({ "anyOf": members })
+                }
+            }
+        }
+        LIST if ty.args.len() == 1 => {
+            json!({ "type": "array", "items": type_to_schema(&ty.args[0]) })
+        }
+        // Other named types have no structure to describe, so they accept any value.
+        _ => json!({ "title": ty.to_string() }),
+    }
+}
+
+// Exports an Ibis type as a JSON Schema document.
+pub fn export_json_schema(ty: &Ent) -> Value {
+    type_to_schema(&ty.get_type())
+}
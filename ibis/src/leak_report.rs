@@ -0,0 +1,164 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::recipes::{CategoryLeak, Leak, Recipe, TagStep};
+use crate::util::{sort_by_name, Names};
+use crate::{Ent, Sol};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+// All the leaks of one check (or category check), with every source that contributed to them in
+// any of the solutions.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct LeakReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<Ent>,
+    pub node: Ent,
+    pub expected: Ent,
+    pub tags: Vec<Ent>,
+    pub sources: Vec<Ent>,
+    // The shortest paths that a leaked tag took from a source to the node (in each solution).
+    // Sources whose tags reached the node without recorded steps have no path.
+    pub paths: Vec<Vec<Ent>>,
+    // The number of solutions with leaks for this check.
+    pub num_solutions: usize,
+}
+
+impl Names for LeakReport {
+    fn add_names(&self, names: &mut Vec<String>) {
+        self.category.add_names(names);
+        self.node.add_names(names);
+        self.expected.add_names(names);
+        self.tags.add_names(names);
+        self.sources.add_names(names);
+        self.paths.add_names(names);
+    }
+}
+
+type Check = (Option<Ent>, Ent, Ent); // category, node, expected
+
+#[derive(Default)]
+struct Contributions {
+    tags: BTreeSet<Ent>,
+    sources: BTreeSet<Ent>,
+    paths: BTreeSet<Vec<Ent>>,
+    solutions: BTreeSet<Sol>,
+}
+
+// The node that a check applies to (checks on fields apply to a field of the node).
+pub(crate) fn checked_node(node: Ent) -> Ent {
+    node.field_of_node().map_or(node, |(node, _)| node)
+}
+
+fn shortest_path(steps: &[(Ent, Ent)], source: Ent, node: Ent) -> Option<Vec<Ent>> {
+    let mut previous: BTreeMap<Ent, Ent> = BTreeMap::new();
+    let mut todo: VecDeque<Ent> = VecDeque::from([source]);
+    while let Some(current) = todo.pop_front() {
+        if current == node {
+            let mut path = vec![node];
+            let mut current = node;
+            while let Some(prev) = previous.get(&current) {
+                path.push(*prev);
+                current = *prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (from, to) in steps {
+            if *from == current && *to != source && !previous.contains_key(to) {
+                previous.insert(*to, current);
+                todo.push_back(*to);
+            }
+        }
+    }
+    None // The tag reached the node in a way that was not recorded as steps.
+}
+
+// Adds the leaks of a solution to the contributions to each check.
+fn add_solution(
+    checks: &mut BTreeMap<Check, Contributions>,
+    sol: Sol,
+    recipe: &Recipe,
+    steps: &[(Ent, Ent, Ent)],
+) {
+    let mut sources: BTreeMap<Check, BTreeMap<Ent, BTreeSet<Ent>>> = BTreeMap::new(); // source, tags
+    let leaks = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, expected, source, tag)| ((None, *node, *expected), *source, *tag));
+    let category_leaks = recipe.feedback.category_leaks.iter().map(
+        |CategoryLeak(_, category, node, expected, source, tag)| {
+            ((Some(*category), *node, *expected), *source, *tag)
+        },
+    );
+    for (check, source, tag) in leaks.chain(category_leaks) {
+        sources
+            .entry(check)
+            .or_default()
+            .entry(source)
+            .or_default()
+            .insert(tag);
+    }
+    for (check, sources) in sources {
+        let contributions = checks.entry(check).or_default();
+        contributions.solutions.insert(sol);
+        for (source, tags) in sources {
+            let tag_steps: Vec<(Ent, Ent)> = steps
+                .iter()
+                .filter(|(_, _, tag)| tags.contains(tag))
+                .map(|(from, to, _)| (*from, *to))
+                .collect();
+            contributions
+                .paths
+                .extend(shortest_path(&tag_steps, source, checked_node(check.1)));
+            contributions.sources.insert(source);
+            contributions.tags.extend(tags);
+        }
+    }
+}
+
+// Aggregates the leaks of all the recipes by check.
+pub fn leak_reports<'a>(
+    recipes: &[Recipe],
+    steps: impl IntoIterator<Item = &'a TagStep>,
+) -> Vec<LeakReport> {
+    let mut steps_by_sol: BTreeMap<Sol, Vec<(Ent, Ent, Ent)>> = BTreeMap::new();
+    for TagStep(sol, from, to, tag) in steps {
+        steps_by_sol
+            .entry(*sol)
+            .or_default()
+            .push((*from, *to, *tag));
+    }
+    let mut checks: BTreeMap<Check, Contributions> = BTreeMap::new();
+    for recipe in recipes {
+        let sol = recipe.id.unwrap_or_else(|| Sol::from(recipe));
+        let steps = steps_by_sol.get(&sol).map_or(&[][..], |steps| &steps[..]);
+        add_solution(&mut checks, sol, recipe, steps);
+    }
+    let mut reports: Vec<LeakReport> = checks
+        .into_iter()
+        .map(|((category, node, expected), contributions)| {
+            let mut tags: Vec<Ent> = contributions.tags.into_iter().collect();
+            sort_by_name(&mut tags);
+            let mut sources: Vec<Ent> = contributions.sources.into_iter().collect();
+            sort_by_name(&mut sources);
+            let mut paths: Vec<Vec<Ent>> = contributions.paths.into_iter().collect();
+            sort_by_name(&mut paths);
+            LeakReport {
+                category,
+                node,
+                expected,
+                tags,
+                sources,
+                paths,
+                num_solutions: contributions.solutions.len(),
+            }
+        })
+        .collect();
+    sort_by_name(&mut reports);
+    reports
+}
@@ -6,13 +6,23 @@
 #![allow(clippy::unused_unit)] // rustwasm/wasm-bindgen#2774 awaiting next `wasm-bindgen` release
 
 mod context;
+mod counterexample;
+mod diff;
 mod ent;
 mod error;
+mod json_schema;
+mod leak_report;
+mod lint;
+mod policy;
+mod refinement;
+mod repair;
+mod schema_inference;
 mod solution_data;
 mod solution_id;
 mod type_parser;
 mod type_parser_cache;
 mod type_struct;
+mod typescript;
 #[macro_use]
 mod util;
 #[cfg(feature = "d3")]
@@ -23,11 +33,19 @@ pub mod recipes;
 #[cfg(feature = "dot")]
 pub mod to_dot_impls;
 
+pub use counterexample::Counterexample;
+pub use diff::{IbisDiff, LeakChange, SolutionDiff};
 pub use ent::Ent;
 pub use error::IbisError;
+pub use json_schema::{export_json_schema, import_json_schema, JsonSchemaImport};
+pub use leak_report::LeakReport;
+pub use policy::Policy;
 pub use recipes::*;
+pub use repair::{Repair, RepairSuggestion};
+pub use schema_inference::infer_type;
 pub use solution_data::SolutionData;
 pub use solution_id::Sol;
+pub use typescript::{import_typescript, TypeScriptImport};
 pub use util::*;
 
 use shadow_rs::shadow;
@@ -77,9 +95,8 @@ pub fn get_solutions(data: &str, loss: Option<usize>) -> Ibis {
 
     // TODO: Use ibis::Error and https://serde.rs/error-handling.html instead of expect.
     let recipes: Ibis = serde_json::from_str(data)
-        .map_err(|e| {
+        .inspect_err(|_| {
             eprintln!("{}", data);
-            e
         })
         .unwrap_or_else(|e| panic!("JSON Error: {}. In {}", e, data));
     runtime.add_recipes(recipes);
@@ -87,6 +104,15 @@ pub fn get_solutions(data: &str, loss: Option<usize>) -> Ibis {
     runtime.extract_solutions_with_loss(loss)
 }
 
+pub fn infer_schema(data: &str) -> String {
+    // TODO: Use ibis::Error and https://serde.rs/error-handling.html instead of expect.
+    let samples: Vec<serde_json::Value> = serde_json::Deserializer::from_str(data)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("JSON Error: {}. In {}", e, data));
+    infer_type(&samples).to_string()
+}
+
 pub fn version_info() -> String {
     build::version()
 }
@@ -116,6 +142,12 @@ pub mod wasm {
         serde_json::to_string(&solutions).expect("Couldn't serialize Ibis output")
     }
 
+    #[wasm_bindgen]
+    pub fn infer_schema(data: &str) -> String {
+        setup();
+        super::infer_schema(data)
+    }
+
     #[wasm_bindgen]
     pub fn all_solutions(data: &str) -> String {
         setup();
@@ -0,0 +1,256 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::recipes::{Capability, CapabilityImplies, Config, Node, Recipe, SubtypeInput};
+use crate::type_struct::*;
+use crate::{ent, Ent};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Names shorter than this are too often similar by coincidence (e.g. 'Int' and 'Inc').
+const MIN_SIMILAR_NAME_LENGTH: usize = 4;
+
+// Collects the type names used in a type, ignoring labels, capabilities, literals and built-ins.
+fn add_names(ty: &Type, names: &mut BTreeSet<String>) {
+    let args = if ty.name == LABELLED || ty.name == OPTIONAL_LABELLED || ty.name == WITH_CAPABILITY
+    {
+        ty.args.get(1..).unwrap_or_default()
+    } else {
+        if ty.literal_base().is_none() && !ty.name.starts_with("ibis.") {
+            names.insert(ty.name.clone());
+        }
+        &ty.args[..]
+    };
+    for arg in args {
+        add_names(arg, names);
+    }
+}
+
+fn names_of(ent: &Ent) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    add_names(&ent.get_type(), &mut names);
+    names
+}
+
+// The number of single character insertions, deletions, substitutions or transpositions
+// needed to turn a into b.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<Vec<usize>> = (0..=a.len())
+        .map(|i| (0..=b.len()).map(|j| if i == 0 { j } else { i }).collect())
+        .collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+fn is_similar(a: &str, b: &str) -> bool {
+    a.len() >= MIN_SIMILAR_NAME_LENGTH
+        && b.len() >= MIN_SIMILAR_NAME_LENGTH
+        && (a.eq_ignore_ascii_case(b) || edit_distance(a, b) <= 1)
+}
+
+// The types reachable from each type by following the subtypes list.
+fn supertypes(subtypes: &[SubtypeInput]) -> BTreeMap<Ent, BTreeSet<Ent>> {
+    let mut direct: BTreeMap<Ent, BTreeSet<Ent>> = BTreeMap::new();
+    for SubtypeInput(sub, sup) in subtypes {
+        direct.entry(*sub).or_default().insert(*sup);
+    }
+    let mut reachable = BTreeMap::new();
+    for start in direct.keys() {
+        let mut seen: BTreeSet<Ent> = BTreeSet::new();
+        let mut todo: Vec<Ent> = vec![*start];
+        while let Some(current) = todo.pop() {
+            for next in direct.get(&current).into_iter().flatten() {
+                if seen.insert(*next) {
+                    todo.push(*next);
+                }
+            }
+        }
+        reachable.insert(*start, seen);
+    }
+    reachable
+}
+
+impl Config {
+    fn lint_equivalences(&self, warnings: &mut Vec<String>) {
+        let reachable = supertypes(&self.subtypes);
+        let mut reported: BTreeSet<Ent> = BTreeSet::new();
+        for (ty, supers) in &reachable {
+            if reported.contains(ty) {
+                continue;
+            }
+            let mut class: Vec<String> = supers
+                .iter()
+                .filter(|sup| reachable.get(sup).is_some_and(|back| back.contains(ty)))
+                .map(|sup| {
+                    reported.insert(*sup);
+                    format!("'{}'", sup)
+                })
+                .collect();
+            class.sort();
+            if class.len() > 1 {
+                warnings.push(format!(
+                    "Types {} are subtypes of each other, so they are equivalent",
+                    class.join(", ")
+                ));
+            }
+        }
+    }
+
+    fn generic_kinds(&self) -> BTreeMap<Ent, BTreeSet<&'static str>> {
+        let mut kinds: BTreeMap<Ent, BTreeSet<&'static str>> = BTreeMap::new();
+        for SubtypeInput(sub, sup) in &self.subtypes {
+            for kind in [GENERIC, INDUCTIVE] {
+                if *sup == ent!(kind) {
+                    kinds.entry(*sub).or_default().insert(kind);
+                }
+            }
+        }
+        kinds
+    }
+
+    fn lint_generics(&self, warnings: &mut Vec<String>) {
+        let kinds = self.generic_kinds();
+        let describe =
+            |kinds: &BTreeSet<&str>| kinds.iter().cloned().collect::<Vec<&str>>().join(" and ");
+        for SubtypeInput(sub, sup) in &self.subtypes {
+            // Arguments are only related between generics that are declared the same way.
+            if let (Some(sub_kinds), Some(sup_kinds)) = (kinds.get(sub), kinds.get(sup)) {
+                if sub_kinds == sup_kinds {
+                    continue;
+                }
+                warnings.push(format!(
+                    "Generic type '{}' ({}) is declared a subtype of '{}' ({}), so their arguments will not be related",
+                    sub,
+                    describe(sub_kinds),
+                    sup,
+                    describe(sup_kinds)
+                ));
+            }
+        }
+    }
+
+    fn declared_names(&self) -> BTreeSet<String> {
+        let mut declared: BTreeSet<String> = self.json_schemas.keys().cloned().collect();
+        for SubtypeInput(sub, sup) in &self.subtypes {
+            declared.extend(names_of(sub));
+            declared.extend(names_of(sup));
+        }
+        declared
+    }
+
+    fn lint_unused(&self, used: &BTreeMap<String, Ent>, warnings: &mut Vec<String>) {
+        // Types are connected if they appear together in a declaration, in either direction.
+        let mut connected: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for SubtypeInput(sub, sup) in &self.subtypes {
+            let names: BTreeSet<String> = names_of(sub).into_iter().chain(names_of(sup)).collect();
+            for name in &names {
+                connected
+                    .entry(name.clone())
+                    .or_default()
+                    .extend(names.iter().cloned());
+            }
+        }
+        let mut reachable: BTreeSet<String> = used.keys().cloned().collect();
+        let mut todo: Vec<String> = reachable.iter().cloned().collect();
+        while let Some(current) = todo.pop() {
+            for next in connected.get(&current).into_iter().flatten() {
+                if reachable.insert(next.clone()) {
+                    todo.push(next.clone());
+                }
+            }
+        }
+        let mut capabilities: BTreeSet<String> = BTreeSet::new();
+        for Capability(from, to) in &self.capabilities {
+            capabilities.extend([from.to_string(), to.to_string()]);
+        }
+        for CapabilityImplies(stronger, weaker) in &self.capability_implies {
+            capabilities.extend([stronger.to_string(), weaker.to_string()]);
+        }
+        for name in connected.keys() {
+            if !reachable.contains(name) && !capabilities.contains(name) {
+                warnings.push(format!(
+                    "Type '{}' is declared but is not related to the type of any node",
+                    name
+                ));
+            }
+        }
+    }
+
+    // Reports likely mistakes in the subtypes list, and in the types used by the recipes' nodes.
+    pub fn lint<'a>(&self, recipes: impl IntoIterator<Item = &'a Recipe>) -> Vec<String> {
+        let mut warnings = vec![];
+        self.lint_equivalences(&mut warnings);
+        self.lint_generics(&mut warnings);
+
+        let mut used: BTreeMap<String, Ent> = BTreeMap::new(); // name, first node using it.
+        for recipe in recipes {
+            for Node(_, node, ty) in &recipe.nodes {
+                for name in names_of(ty) {
+                    used.entry(name).or_insert(*node);
+                }
+            }
+        }
+        let declared = self.declared_names();
+        if !used.is_empty() {
+            self.lint_unused(&used, &mut warnings);
+        }
+        for (name, node) in &used {
+            if !declared.contains(name) {
+                warnings.push(format!(
+                    "Type '{}' is used by node '{}' but is never declared",
+                    name, node
+                ));
+            }
+        }
+        let all_names: BTreeSet<&String> = declared.iter().chain(used.keys()).collect();
+        for name in &all_names {
+            for other in all_names
+                .range::<&String, _>((std::ops::Bound::Excluded(name), std::ops::Bound::Unbounded))
+            {
+                if is_similar(name, other) {
+                    warnings.push(format!(
+                        "Types '{}' and '{}' have similar names, is one a typo?",
+                        name, other
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_character_changes() {
+        assert_eq!(edit_distance("Image", "Image"), 0);
+        assert_eq!(edit_distance("Image", "Imgae"), 1);
+        assert_eq!(edit_distance("Image", "Images"), 1);
+        assert_eq!(edit_distance("Image", "Imag"), 1);
+        assert_eq!(edit_distance("Image", "Inage"), 1);
+    }
+
+    #[test]
+    fn short_names_are_not_similar() {
+        assert!(!is_similar("Int", "Inc"));
+        assert!(is_similar("Text", "Test"));
+        assert!(is_similar("Person", "person"));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+extern crate nom;
+use crate::recipes::{
+    CategoryPolicy, PolicyAnd, PolicyCheck, PolicyFrom, PolicyNotFrom, PolicyNotWithin, PolicyOr,
+    PolicyWithin,
+};
+use crate::type_struct::Type;
+use crate::Ent;
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{char, multispace0, satisfy},
+    combinator::{all_consuming, map, not, peek, recognize, verify},
+    multi::{many1, separated_list1},
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+use std::sync::Arc;
+
+const KEYWORDS: [&str; 3] = ["AND", "OR", "NOT"];
+
+const POLICY: &str = "ibis.Policy";
+
+// A policy on the tags that may reach a node, e.g. 'public OR (consented AND NOT location)'.
+// Policies are checked against each tag reaching the node separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    // The tag is at most as private as this one, e.g. 'public'. Negated, it is the complement: the
+    // tag is not at most as private as this one (e.g. 'NOT location' forbids location and public,
+    // but allows tags that are more private than location or unrelated to it).
+    Tag(String),
+    // The tag was claimed by one of the particle's nodes, e.g. 'tag from p_camera'.
+    FromParticle(String),
+    Not(Box<Policy>),
+    And(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+}
+
+impl std::fmt::Display for Policy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nested = |policy: &Policy| match policy {
+            Policy::And(_, _) | Policy::Or(_, _) => format!("({})", policy),
+            _ => policy.to_string(),
+        };
+        match self {
+            Policy::Tag(tag) => write!(f, "{}", tag),
+            Policy::FromParticle(particle) => write!(f, "tag from {}", particle),
+            Policy::Not(inner) => match &**inner {
+                Policy::FromParticle(particle) => write!(f, "no tag from {}", particle),
+                inner => write!(f, "NOT {}", nested(inner)),
+            },
+            Policy::And(left, right) => write!(f, "{} AND {}", nested(left), nested(right)),
+            Policy::Or(left, right) => write!(f, "{} OR {}", nested(left), nested(right)),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+fn keyword<'a>(k: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    preceded(
+        multispace0,
+        terminated(tag_no_case(k), not(peek(satisfy(is_name_char)))),
+    )
+}
+
+fn name(input: &str) -> IResult<&str, String> {
+    map(
+        verify(
+            preceded(multispace0, recognize(many1(satisfy(is_name_char)))),
+            |name: &str| !KEYWORDS.contains(&name.to_uppercase().as_str()),
+        ),
+        |name: &str| name.to_string(),
+    )(input)
+}
+
+fn fold(mut policies: Vec<Policy>, op: fn(Box<Policy>, Box<Policy>) -> Policy) -> Policy {
+    let first = policies.remove(0);
+    policies
+        .into_iter()
+        .fold(first, |left, right| op(Box::new(left), Box::new(right)))
+}
+
+fn unary(input: &str) -> IResult<&str, Policy> {
+    alt((
+        map(
+            preceded(
+                tuple((keyword("no"), keyword("tag"), keyword("from"))),
+                name,
+            ),
+            |particle| Policy::Not(Box::new(Policy::FromParticle(particle))),
+        ),
+        map(
+            preceded(tuple((keyword("tag"), keyword("from"))), name),
+            Policy::FromParticle,
+        ),
+        map(preceded(keyword("NOT"), unary), |inner| {
+            Policy::Not(Box::new(inner))
+        }),
+        delimited(
+            preceded(multispace0, char('(')),
+            disjunction,
+            preceded(multispace0, char(')')),
+        ),
+        map(name, Policy::Tag),
+    ))(input)
+}
+
+fn conjunction(input: &str) -> IResult<&str, Policy> {
+    map(separated_list1(keyword("AND"), unary), |policies| {
+        fold(policies, Policy::And)
+    })(input)
+}
+
+fn disjunction(input: &str) -> IResult<&str, Policy> {
+    map(separated_list1(keyword("OR"), conjunction), |policies| {
+        fold(policies, Policy::Or)
+    })(input)
+}
+
+// The facts that the policy rules in recipes.rs use to check tags against policies.
+#[derive(Debug, Default)]
+pub struct PolicyFacts {
+    pub checks: Vec<PolicyCheck>,
+    pub category_checks: Vec<CategoryPolicy>,
+    pub within: Vec<PolicyWithin>,
+    pub not_within: Vec<PolicyNotWithin>,
+    pub from: Vec<PolicyFrom>,
+    pub not_from: Vec<PolicyNotFrom>,
+    pub and: Vec<PolicyAnd>,
+    pub or: Vec<PolicyOr>,
+}
+
+fn id(policy: &Policy) -> Ent {
+    // Policies are identified by their text, so they are readable in leaks, but are kept apart
+    // from tags (e.g. the policy 'location' is not the tag 'location').
+    Ent::by_type(Arc::new(
+        Type::new(POLICY).with_arg(Type::new(&policy.to_string())),
+    ))
+}
+
+impl Policy {
+    pub fn parse(input: &str) -> Result<Policy, String> {
+        all_consuming(terminated(disjunction, multispace0))(input)
+            .map(|(_, policy)| policy)
+            .map_err(|_| format!("Could not parse policy '{}'", input))
+    }
+
+    // Adds facts for this policy (or its negation) and returns its id. Negations are pushed down
+    // to the tags and particles, so the rules only need negation on input relations.
+    fn compile(&self, negated: bool, facts: &mut PolicyFacts) -> Ent {
+        let policy = if negated {
+            Policy::Not(Box::new(self.clone()))
+        } else {
+            self.clone()
+        };
+        let policy_id = id(&policy);
+        match (self, negated) {
+            (Policy::Tag(tag), false) => facts.within.push(PolicyWithin(policy_id, ent(tag))),
+            (Policy::Tag(tag), true) => facts.not_within.push(PolicyNotWithin(policy_id, ent(tag))),
+            (Policy::FromParticle(particle), false) => {
+                facts.from.push(PolicyFrom(policy_id, ent(particle)))
+            }
+            (Policy::FromParticle(particle), true) => {
+                facts.not_from.push(PolicyNotFrom(policy_id, ent(particle)))
+            }
+            (Policy::Not(inner), negated) => {
+                let inner_id = inner.compile(!negated, facts);
+                if inner_id != policy_id {
+                    // A double negation holds whenever the inner policy does.
+                    facts.and.push(PolicyAnd(policy_id, inner_id, inner_id));
+                }
+            }
+            (Policy::And(left, right), false) | (Policy::Or(left, right), true) => {
+                let left = left.compile(negated, facts);
+                let right = right.compile(negated, facts);
+                facts.and.push(PolicyAnd(policy_id, left, right));
+            }
+            (Policy::Or(left, right), false) | (Policy::And(left, right), true) => {
+                let left = left.compile(negated, facts);
+                let right = right.compile(negated, facts);
+                facts.or.push(PolicyOr(policy_id, left, right));
+            }
+        }
+        policy_id
+    }
+
+    pub fn compile_check(&self, node: Ent, facts: &mut PolicyFacts) {
+        let policy_id = self.compile(false, facts);
+        facts.checks.push(PolicyCheck(node, policy_id));
+    }
+
+    pub fn compile_category_check(&self, category: Ent, facts: &mut PolicyFacts) {
+        let policy_id = self.compile(false, facts);
+        facts
+            .category_checks
+            .push(CategoryPolicy(category, policy_id));
+    }
+}
+
+fn ent(name: &str) -> Ent {
+    Ent::by_type(Arc::new(Type::new(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_policies_with_precedence() {
+        let policy = Policy::parse("public OR consented AND NOT location").unwrap();
+        assert_eq!(policy.to_string(), "public OR (consented AND NOT location)");
+        let policy = Policy::parse("(public OR consented) and no tag from p_camera").unwrap();
+        assert_eq!(
+            policy.to_string(),
+            "(public OR consented) AND no tag from p_camera"
+        );
+    }
+
+    #[test]
+    fn policies_are_not_tags() {
+        let policy = Policy::parse("location").unwrap();
+        assert_ne!(id(&policy), ent("location"));
+        assert_eq!(id(&policy).to_string(), "ibis.Policy(location)");
+    }
+
+    #[test]
+    fn reject_malformed_policies() {
+        assert!(Policy::parse("public OR").is_err());
+        assert!(Policy::parse("(public").is_err());
+        assert!(Policy::parse("").is_err());
+    }
+}
@@ -5,8 +5,12 @@
 // https://developers.google.com/open-source/licenses/bsd
 #![allow(clippy::collapsible_if)]
 
+use crate::ent::deserialize_capability;
+use crate::json_schema::import_json_schema;
+use crate::leak_report::{leak_reports, LeakReport};
+use crate::policy::{Policy, PolicyFacts};
 use crate::type_struct::*;
-use crate::util::make;
+use crate::util::{make, sort_by_name, Names};
 use crate::{apply, ent, name, Ent, Sol, SolutionData};
 use crepe::crepe;
 use serde::{Deserialize, Serialize};
@@ -37,7 +41,24 @@ crepe! {
     pub struct LessPrivateThan(pub Ent, pub Ent); // tag, tag
     @input
     #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
-    pub struct Capability(pub Ent, pub Ent); // cap from, cap to
+    pub struct Capability(
+        #[serde(deserialize_with = "deserialize_capability")] pub Ent,
+        #[serde(deserialize_with = "deserialize_capability")] pub Ent,
+    ); // cap from, cap to
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct CapabilityImplies(
+        #[serde(deserialize_with = "deserialize_capability")] pub Ent,
+        #[serde(deserialize_with = "deserialize_capability")] pub Ent,
+    ); // stronger cap, weaker cap
+
+    struct DeclaredCapability(Ent);
+    DeclaredCapability(from) <- Capability(from, _);
+    DeclaredCapability(to) <- Capability(_, to);
+    DeclaredCapability(stronger) <- CapabilityImplies(stronger, _);
+    DeclaredCapability(weaker) <- CapabilityImplies(_, weaker);
+    DeclaredCapability(cap) <- Subtype(cap, declared), DeclaredCapability(declared);
+    Subtype(stronger, weaker) <- CapabilityImplies(stronger, weaker); // e.g. readwrite is a read
 
     @input
     #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
@@ -48,6 +69,8 @@ crepe! {
 
     struct CompatibleWith(pub Ent, pub Ent); // from, to
     struct HasCapability(pub Ent, pub Ent); // cap, ty
+    struct UsesCapability(pub Ent, pub Ent); // cap, ty (i.e. the capabilities written in the type)
+    struct CapabilityAllows(pub Ent, pub Ent, pub Ent, pub Ent); // from, to, cap from, cap to
 
     @input
     #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
@@ -64,6 +87,69 @@ crepe! {
     @input
     #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
     pub struct TrustedToRemoveTagFromNode(pub Ent, pub Ent); // node, node from
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct TrustedToRemoveTagInto(pub Ent, pub Ent, pub Ent); // node, tag, destination node
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct TrustedToRemoveTagForPurpose(pub Ent, pub Ent, pub Ent); // node, tag, purpose
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct Purpose(pub Ent, pub Ent); // node, purpose
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct InternalFlow(pub Ent, pub Ent, pub Ent); // particle, input node, output node
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct NoInternalFlow(pub Ent); // particle
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct ControlInput(pub Ent); // input node that decides which outputs a particle writes
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyCheck(pub Ent, pub Ent); // node, policy
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyWithin(pub Ent, pub Ent); // policy, tag (at most as private as the tag)
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyNotWithin(pub Ent, pub Ent); // policy, tag
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyFrom(pub Ent, pub Ent); // policy, particle (claimed by the particle)
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyNotFrom(pub Ent, pub Ent); // policy, particle
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyAnd(pub Ent, pub Ent, pub Ent); // policy, policy, policy
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PolicyOr(pub Ent, pub Ent, pub Ent); // policy, policy, policy
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct ParticleCategory(pub Ent, pub Ent); // particle, sink category (e.g. network)
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct TypeCategory(pub Ent, pub Ent); // type, sink category
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct CategoryCheck(pub Ent, pub Ent); // sink category, tag
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct CategoryPolicy(pub Ent, pub Ent); // sink category, policy
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct LessTrustedThan(pub Ent, pub Ent); // integrity tag, integrity tag
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct IntegrityClaim(pub Ent, pub Ent); // identifier, integrity tag
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct IntegrityCheck(pub Ent, pub Ent); // identifier, required integrity tag
+    @input
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct TrustedToEndorse(pub Ent, pub Ent); // node, integrity tag
 
     // Feedback
     @output
@@ -75,6 +161,36 @@ crepe! {
     @output
     #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
     pub struct TypeError(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent); // sol, node, ty, source, ty
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct EdgeCapability(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent); // sol, from, to, cap from, cap to
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct UndeclaredCapability(pub Ent, pub Ent); // node, cap
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct PrivacyLabel(pub Sol, pub Ent, pub Ent); // sol, node, join of the node's tags
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct HasIntegrityTag(pub Sol, pub Ent, pub Ent, pub Ent); // solution, source node, node with tag, integrity tag
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct HasFieldTag(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent); // solution, source node, node with tag, label, tag
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct IntegrityViolation(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent); // sol, node, required_tag, source, tag2
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct Declassification(pub Sol, pub Ent, pub Ent, pub Ent); // sol, from, to, tag
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct ImplicitFlow(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent); // sol, node, expected_tag, source, tag2
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct CategoryLeak(pub Sol, pub Ent, pub Ent, pub Ent, pub Ent, pub Ent); // sol, category, node, expected_tag, source, tag2
+    @output
+    #[derive(Debug, Ord, PartialOrd, Serialize, Deserialize)]
+    pub struct TagStep(pub Sol, pub Ent, pub Ent, pub Ent); // sol, from, to, tag
     UncheckedSolution(parent.add_edge(from, to)) <-
         FlagEnabled(PLANNING, true),
         Node(_from_particle, from, from_type),
@@ -101,9 +217,9 @@ crepe! {
         KnownType(y),
         (!y.is_a(WITH_CAPABILITY)),
         // ({eprintln!("checking subtyping ({}) ({})", x, y); true}),
-        Subtype(x, y);
+        WidthSubtype(x, y);
 
-    CompatibleWith(x, y) <- // Check that y has the capabilities required by x.
+    CapabilityAllows(x, y, x_cap, y_cap) <- // Check that y has the capabilities required by x.
         KnownType(x),
         (x.is_a(WITH_CAPABILITY)),
         KnownType(y),
@@ -111,6 +227,31 @@ crepe! {
         Subtype(x.args()[0], x_cap),
         Capability(x_cap, y_cap), // If this one is supported we can continue.
         CompatibleWith(x.args()[1], y);
+    CompatibleWith(x, y) <- CapabilityAllows(x, y, _, _);
+
+    struct CapabilityJustifies(Ent, Ent, Ent, Ent); // from, to, cap from, cap to
+    CapabilityJustifies(x, y, x_cap, y_cap) <- CapabilityAllows(x, y, x_cap, y_cap);
+    CapabilityJustifies(x, y, x_cap, y_cap) <- // Include the pairs used by nested capabilities.
+        CapabilityAllows(x, y, _, _),
+        CapabilityJustifies(x.args()[1], y, x_cap, y_cap);
+
+    UsesCapability(ty.args()[0], ty) <-
+        KnownType(ty),
+        (ty.is_a(WITH_CAPABILITY));
+    UsesCapability(cap, ty) <-
+        KnownType(ty),
+        (ty.is_a(WITH_CAPABILITY)),
+        UsesCapability(cap, ty.args()[1]);
+
+    Subtype(cap, pattern) <- // Parameterised capabilities match patterns like write(*).
+        UsesCapability(cap, _),
+        DeclaredCapability(pattern),
+        (cap.matches_capability_pattern(pattern));
+
+    UndeclaredCapability(node, cap) <-
+        Node(_particle, node, ty),
+        UsesCapability(cap, ty),
+        !DeclaredCapability(cap);
 
     CompatibleWith(x, y) <- // If a type has no capabilities, discard the capabilities of it's possible super type.
         KnownType(x),
@@ -120,54 +261,72 @@ crepe! {
         // ({eprintln!("discarding capability from y ({}) ({})", x, y); true}),
         CompatibleWith(x, y.args()[1]);
 
+    // The structure of the known types, so that rules can join on it rather than inspecting every
+    // pair of types.
+    struct Product(Ent, usize); // product, number of fields
+    Product(prod, prod.num_args()) <- KnownType(prod), (prod.is_a(PRODUCT));
+    struct ProductField(Ent, usize, Ent); // product, index, field
+    ProductField(prod, n, field) <-
+        Product(prod, _),
+        for (n, field) in prod.args().into_iter().enumerate();
+    struct Union(Ent, usize); // union, number of members
+    Union(union_type, union_type.num_args()) <- KnownType(union_type), (union_type.is_a(UNION));
+    struct UnionMember(Ent, usize, Ent); // union, index, member
+    UnionMember(union_type, n, member) <-
+        Union(union_type, _),
+        for (n, member) in union_type.args().into_iter().enumerate();
+    struct Field(Ent, Ent, Ent, bool); // field, label, type, optional
+    Field(field, field.args()[0], field.args()[1], false) <-
+        KnownType(field),
+        (field.is_a(LABELLED));
+    Field(field, field.args()[0], field.args()[1], true) <-
+        KnownType(field),
+        (field.is_a(OPTIONAL_LABELLED));
+    struct Refined(Ent, Ent); // refinement, the type it refines
+    Refined(refined, base) <- KnownType(refined), let Some(base) = refined.unrefined();
+    struct Literal(Ent, Ent); // literal, its base type
+    Literal(literal, ent!(base)) <- KnownType(literal), let Some(base) = literal.literal_base();
+
     // TODO: Replace with the 'all' aggregate when it exists.
     // See https://github.com/ekzhang/crepe/issues/10
     struct SubtypesAllArgs(Ent, Ent, usize);
-    SubtypesAllArgs(x, y, 0) <- KnownType(x), KnownType(y);
+    SubtypesAllArgs(x, prod, 0) <- Product(prod, _), KnownType(x);
     SubtypesAllArgs(x, y, n+1) <-
         SubtypesAllArgs(x, y, n),
-        (n < y.num_args()),
-        Subtype(x, y.args()[n]);
+        ProductField(y, n, field),
+        Subtype(x, field);
 
     // TODO: Replace with the 'all' aggregate when it exists.
     // See https://github.com/ekzhang/crepe/issues/10
     struct SupertypesAllArgs(Ent, Ent, usize);
-    SupertypesAllArgs(x, y, 0) <- KnownType(x), KnownType(y);
+    SupertypesAllArgs(x, union_type, 0) <- Union(union_type, _), KnownType(x);
     SupertypesAllArgs(x, y, n+1) <-
         SupertypesAllArgs(x, y, n),
-        (n < y.num_args()),
-        Subtype(y.args()[n], x);
+        UnionMember(y, n, member),
+        Subtype(member, x);
 
-    Subtype(x, prod) <-
-        KnownType(prod),
-        (prod.is_a(PRODUCT)),
-        KnownType(x),
-        SubtypesAllArgs(x, prod, prod.num_args());
+    Subtype(x, prod) <- Product(prod, len), SubtypesAllArgs(x, prod, len);
 
-    Subtype(
-        prod,
-        arg
-    ) <-
-        KnownType(prod),
-        (prod.is_a(PRODUCT)),
-        for arg in prod.args();
+    Subtype(prod, field) <- ProductField(prod, _, field);
 
-    Subtype(
-        union_type,
-        x
-    ) <-
-        KnownType(union_type),
-        (union_type.is_a(UNION)),
-        KnownType(x),
-        SupertypesAllArgs(x, union_type, union_type.num_args());
+    Subtype(union_type, x) <- Union(union_type, len), SupertypesAllArgs(x, union_type, len);
 
-    Subtype(
-        arg,
-        union_type
-    ) <-
-        KnownType(union_type),
-        (union_type.is_a(UNION)),
-        for arg in union_type.args();
+    Subtype(arg, union_type) <- UnionMember(union_type, _, arg);
+
+    Subtype(literal, base) <- Literal(literal, base); // Literals are subtypes of their base type.
+
+    // Refinements are subtypes of the type they refine.
+    Subtype(refined, base) <- Refined(refined, base);
+
+    Subtype(x, y) <- // Refinements of the same type are ordered by implication.
+        Refined(x, base),
+        Refined(y, base),
+        (x.refinement_implies(y));
+
+    Subtype(literal, refined) <- // Literals are subtypes of refinements they satisfy.
+        Literal(literal, base),
+        Refined(refined, base),
+        (literal.satisfies_refinement_of(refined));
 
     Subtype(
         labelled,
@@ -184,6 +343,43 @@ crepe! {
         (labelled.is_a(LABELLED)),
         Subtype(labelled.args()[1], sup);
 
+    Subtype(field, optional) <- // A field (optional or otherwise) can be used as an optional field.
+        Field(optional, label, optional_ty, true),
+        Field(field, label, ty, _),
+        Subtype(ty, optional_ty);
+
+    struct HasField(Ent, Ent); // type, label
+    HasField(x, label) <- Field(field, label, _, _), Subtype(x, field);
+
+    // Subtyping that also accepts products that are missing some optional fields.
+    // Unlike Subtype, this is not transitive (e.g. {a: String} <: {} <: {a?: Int}), so it is only
+    // used for checking compatibility.
+    struct WidthSubtype(Ent, Ent); // sub, super
+    WidthSubtype(x, y) <- Subtype(x, y);
+
+    // TODO: Replace with the 'all' aggregate when it exists.
+    // See https://github.com/ekzhang/crepe/issues/10
+    struct WidthSubtypesAllArgs(Ent, Ent, usize);
+    WidthSubtypesAllArgs(x, prod, 0) <- Product(prod, _), KnownType(x);
+    WidthSubtypesAllArgs(x, prod, n+1) <-
+        WidthSubtypesAllArgs(x, prod, n),
+        ProductField(prod, n, field),
+        WidthSubtype(x, field);
+    WidthSubtypesAllArgs(x, prod, n+1) <- // Missing optional fields are allowed.
+        WidthSubtypesAllArgs(x, prod, n),
+        ProductField(prod, n, field),
+        Field(field, label, _, true),
+        !HasField(x, label);
+
+    WidthSubtype(x, prod) <- Product(prod, len), WidthSubtypesAllArgs(x, prod, len);
+
+    WidthSubtype(x, field) <- // Fields may be missing optional fields of their own.
+        Field(field, label, ty, optional),
+        Field(x_field, label, x_ty, x_optional),
+        (optional || !x_optional),
+        Subtype(x, x_field),
+        WidthSubtype(x_ty, ty);
+
     Subtype(
         apply!(x_generic, x_arg),
         apply!(y_generic, y_arg)
@@ -197,30 +393,424 @@ crepe! {
         KnownType(apply!(x_generic, x_arg)),
         KnownType(apply!(y_generic, y_arg));
 
+    // Particles can declare which of their inputs flow to which of their outputs. Otherwise, every
+    // input is assumed to flow to every output.
+    struct DeclaresInternalFlows(Ent); // particle
+    DeclaresInternalFlows(particle) <- InternalFlow(particle, _, _);
+    DeclaresInternalFlows(particle) <- NoInternalFlow(particle);
+
+    struct ParticleFlow(Ent, Ent); // Data may flow 'across stream' (i.e. inside a particle)
+    ParticleFlow(curr, down) <-
+        InternalFlow(particle, curr, down),
+        Node(particle, curr, _),
+        Node(particle, down, _);
+    ParticleFlow(curr, down) <-
+        Node(particle, curr, curr_ty),
+        !DeclaresInternalFlows(particle),
+        HasCapability(curr_cap, curr_ty),
+        Capability(_, curr_cap), // Is input (e.g. read)
+        Node(particle, down, down_ty),
+        (curr != down),
+        HasCapability(down_cap, down_ty), // Has to be able to output it.
+        Capability(down_cap, _); // Is output (e.g. write)
+
+    // Declassification that only applies to data flowing into particular nodes.
+    struct Declassifies(Ent, Ent, Ent); // from, to, tag
+    Declassifies(from, to, tag) <- TrustedToRemoveTagInto(from, tag, to);
+    Declassifies(from, to, tag) <-
+        TrustedToRemoveTagForPurpose(from, tag, purpose),
+        Purpose(to, purpose);
+
     HasTag(s, n, n, tag) <- UncheckedSolution(s), Claim(n, tag);
     HasTag(s, source, *down, tag) <- // Propagate tags 'downstream'
         HasTag(s, source, curr, tag),
         for (up, down) in &s.solution().edges,
         (*up == curr),
         !TrustedToRemoveTag(*down, tag),
-        !TrustedToRemoveTagFromNode(*down, curr);
+        !TrustedToRemoveTagFromNode(*down, curr),
+        !Declassifies(curr, *down, tag);
 
     HasTag(s, source, down, tag) <- // Propagate tags 'across stream' (i.e. inside a particle)
         HasTag(s, source, curr, tag),
-        Node(particle, curr, curr_ty),
-        HasCapability(curr_cap, curr_ty),
-        Capability(_, curr_cap), // Is input (e.g. read)
-        Node(particle, down, down_ty),
-        (curr != down),
+        ParticleFlow(curr, down),
         !TrustedToRemoveTag(down, tag),
         !TrustedToRemoveTagFromNode(down, curr),
-        HasCapability(down_cap, down_ty), // Has to be able to output it.
-        Capability(down_cap, _); // Is output (e.g. write)
+        !Declassifies(curr, down, tag);
 
-    Leak(s, n, t1, source, t2) <-
-        Check(n, t1),
+    // Tags can be claimed on a labelled field of a node's type (e.g. person.ssn). These follow the
+    // field along edges into nodes whose types have the same field, so readers of other fields
+    // do not receive them. Readers whose types do not clearly exclude the field (e.g. '*', a named
+    // type or a union) receive them as tags on the whole node, as do the outputs of a particle.
+    struct DataType(Ent, Ent); // type, type without its capabilities
+    DataType(ty, ty) <- KnownType(ty), (!ty.is_a(WITH_CAPABILITY));
+    DataType(ty, data) <- KnownType(ty), (ty.is_a(WITH_CAPABILITY)), DataType(ty.args()[1], data);
+
+    struct NodeHasField(Ent, Ent); // node, label
+    NodeHasField(node, label) <- Node(_, node, ty), DataType(ty, data), HasField(data, label);
+
+    struct ProjectsFields(Ent); // node, whose type only has the fields that it lists
+    ProjectsFields(node) <-
+        Node(_, node, ty),
+        DataType(ty, data),
+        (data.is_a(PRODUCT) || data.is_a(LABELLED) || data.is_a(OPTIONAL_LABELLED));
+
+    HasFieldTag(s, node, node, label, tag) <-
+        UncheckedSolution(s),
+        Claim(field, tag),
+        let Some((node, label)) = field.field_of_node(),
+        NodeHasField(node, label);
+    HasFieldTag(s, source, *down, label, tag) <- // Propagate field tags 'downstream'
+        HasFieldTag(s, source, curr, label, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        NodeHasField(*down, label),
+        !TrustedToRemoveTag(*down, tag),
+        !TrustedToRemoveTagFromNode(*down, curr),
+        !Declassifies(curr, *down, tag);
+    HasTag(s, source, *down, tag) <- // Propagate field tags as whole node tags to other readers
+        HasFieldTag(s, source, curr, label, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        !NodeHasField(*down, label),
+        !ProjectsFields(*down),
+        !TrustedToRemoveTag(*down, tag),
+        !TrustedToRemoveTagFromNode(*down, curr),
+        !Declassifies(curr, *down, tag);
+    HasTag(s, source, down, tag) <- // Propagate field tags 'across stream' as whole node tags
+        HasFieldTag(s, source, curr, _, tag),
+        ParticleFlow(curr, down),
+        !TrustedToRemoveTag(down, tag),
+        !TrustedToRemoveTagFromNode(down, curr),
+        !Declassifies(curr, down, tag);
+
+    struct TagFlowsTo(Sol, Ent, Ent, Ent); // sol, from, to, tag (before any declassification)
+    TagFlowsTo(s, curr, *down, tag) <-
+        HasTag(s, _, curr, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr);
+    TagFlowsTo(s, curr, down, tag) <- HasTag(s, _, curr, tag), ParticleFlow(curr, down);
+    TagFlowsTo(s, curr, *down, tag) <-
+        HasFieldTag(s, _, curr, label, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        NodeHasField(*down, label);
+    TagFlowsTo(s, curr, *down, tag) <-
+        HasFieldTag(s, _, curr, label, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        !NodeHasField(*down, label),
+        !ProjectsFields(*down);
+    TagFlowsTo(s, curr, down, tag) <- HasFieldTag(s, _, curr, _, tag), ParticleFlow(curr, down);
+
+    // The steps that tags took, used to find the paths of leaks (see leak_report.rs).
+    TagStep(s, from, to, tag) <- TagFlowsTo(s, from, to, tag), !Declassification(s, from, to, tag);
+
+    // An audit of the declassifications that were needed by each solution.
+    Declassification(s, from, to, tag) <- TagFlowsTo(s, from, to, tag), TrustedToRemoveTag(to, tag);
+    Declassification(s, from, to, tag) <-
+        TagFlowsTo(s, from, to, tag),
+        TrustedToRemoveTagFromNode(to, from);
+    Declassification(s, from, to, tag) <- TagFlowsTo(s, from, to, tag), Declassifies(from, to, tag);
+
+    // The security lattice of tags ordered by privacy, with ibis.Public and ibis.Private as its
+    // bottom and top. Every tag that is claimed or checked is in the lattice, even if it is not
+    // ordered relative to any other tag.
+    struct PrivacyTag(Ent);
+    PrivacyTag(ent!(PUBLIC)) <- Seed(_);
+    PrivacyTag(ent!(PRIVATE)) <- Seed(_);
+    PrivacyTag(t) <- LessPrivateThan(t, _);
+    PrivacyTag(t) <- LessPrivateThan(_, t);
+    PrivacyTag(t) <- Claim(_, t);
+    PrivacyTag(t) <- Check(_, t);
+    PrivacyTag(t) <- CategoryCheck(_, t);
+    PrivacyTag(t) <- PolicyWithin(_, t);
+    PrivacyTag(t) <- PolicyNotWithin(_, t);
+
+    struct LessPrivate(Ent, Ent); // The transitive closure of LessPrivateThan
+    LessPrivate(t1, t2) <- LessPrivateThan(t1, t2);
+    LessPrivate(t1, t3) <- LessPrivate(t1, t2), LessPrivate(t2, t3);
+    LessPrivate(ent!(PUBLIC), t) <- PrivacyTag(t), (t != ent!(PUBLIC));
+    LessPrivate(t, ent!(PRIVATE)) <- PrivacyTag(t), (t != ent!(PRIVATE));
+
+    struct AtMostAsPrivate(Ent, Ent);
+    AtMostAsPrivate(t, t) <- PrivacyTag(t);
+    AtMostAsPrivate(t1, t2) <- LessPrivate(t1, t2);
+
+    // Only the tags that reach nodes are joined: the claimed tags and those more private than them
+    // (which includes every join of claimed tags).
+    struct JoinableTag(Ent);
+    JoinableTag(t) <- Claim(_, t);
+    JoinableTag(t2) <- JoinableTag(t1), LessPrivate(t1, t2);
+
+    struct UpperBound(Ent, Ent, Ent); // t1, t2, bound
+    UpperBound(t1, t2, bound) <-
+        JoinableTag(t1),
+        JoinableTag(t2),
+        AtMostAsPrivate(t1, bound),
+        AtMostAsPrivate(t2, bound);
+    struct NotLeastUpperBound(Ent, Ent, Ent);
+    NotLeastUpperBound(t1, t2, bound) <-
+        UpperBound(t1, t2, bound),
+        UpperBound(t1, t2, other),
+        !AtMostAsPrivate(bound, other);
+    struct PrivacyJoin(Ent, Ent, Ent); // t1, t2, the least private tag at least as private as both
+    PrivacyJoin(t1, t2, bound) <- UpperBound(t1, t2, bound), !NotLeastUpperBound(t1, t2, bound);
+
+    struct JoinedTags(Sol, Ent, Ent); // sol, node, join of some of the node's tags
+    JoinedTags(s, n, tag) <- HasTag(s, _, n, tag), PrivacyTag(tag);
+    JoinedTags(s, n, join) <- JoinedTags(s, n, t1), JoinedTags(s, n, t2), PrivacyJoin(t1, t2, join);
+    struct NotNodeLabel(Sol, Ent, Ent);
+    NotNodeLabel(s, n, label) <-
+        JoinedTags(s, n, label),
+        JoinedTags(s, n, other),
+        !AtMostAsPrivate(other, label);
+    PrivacyLabel(s, n, label) <- JoinedTags(s, n, label), !NotNodeLabel(s, n, label);
+
+    // Tags that are ordered relative to each other (ignoring ibis.Public and ibis.Private) describe
+    // the same kind of data. Tags of other kinds are independent policies, so a check only applies
+    // to the tags of its own kind (and ibis.Public and ibis.Private, which apply to every tag).
+    struct OrderedTags(Ent, Ent);
+    OrderedTags(t1, t2) <-
         LessPrivateThan(t1, t2),
-        HasTag(s, source, n, t2); // Check failed, node has a 'more private' tag i.e. is leaking.
+        (![t1, t2].iter().any(|t| *t == ent!(PUBLIC) || *t == ent!(PRIVATE)));
+    OrderedTags(t2, t1) <- OrderedTags(t1, t2);
+    struct SameKind(Ent, Ent);
+    SameKind(t, t) <- PrivacyTag(t);
+    SameKind(t1, t3) <- SameKind(t1, t2), OrderedTags(t2, t3);
+    struct AppliesTo(Ent, Ent); // check tag, tag
+    AppliesTo(t1, t2) <- SameKind(t1, t2);
+    AppliesTo(ent!(PUBLIC), t) <- PrivacyTag(t);
+    AppliesTo(ent!(PRIVATE), t) <- PrivacyTag(t);
+    AppliesTo(t, ent!(PUBLIC)) <- PrivacyTag(t);
+    AppliesTo(t, ent!(PRIVATE)) <- PrivacyTag(t);
+
+    struct CheckedTag(Sol, Ent, Ent, Ent); // sol, node (or field), source, tag
+    CheckedTag(s, n, source, tag) <- HasTag(s, source, n, tag);
+    CheckedTag(s, n, source, tag) <- // Checks on a node include the tags on its fields.
+        HasFieldTag(s, source, n, _, tag);
+    CheckedTag(s, field, source, tag) <- // Checks on a field include the tags on its node.
+        Check(field, _),
+        let Some((n, _)) = field.field_of_node(),
+        HasTag(s, source, n, tag);
+    CheckedTag(s, field, source, tag) <-
+        Check(field, _),
+        let Some((n, label)) = field.field_of_node(),
+        HasFieldTag(s, source, n, label, tag);
+
+    // A check passes if the join of the node's tags that it applies to is at most as private as
+    // the checked tag.
+    struct CheckedTags(Sol, Ent, Ent, Ent); // sol, node, check tag, join of some of the tags
+    CheckedTags(s, n, t1, tag) <- Check(n, t1), CheckedTag(s, n, _, tag), AppliesTo(t1, tag);
+    CheckedTags(s, n, t1, join) <-
+        CheckedTags(s, n, t1, a),
+        CheckedTags(s, n, t1, b),
+        PrivacyJoin(a, b, join);
+    struct NotCheckedLabel(Sol, Ent, Ent, Ent);
+    NotCheckedLabel(s, n, t1, label) <-
+        CheckedTags(s, n, t1, label),
+        CheckedTags(s, n, t1, other),
+        !AtMostAsPrivate(other, label);
+    struct CheckedLabel(Sol, Ent, Ent, Ent); // sol, node, check tag, label
+    CheckedLabel(s, n, t1, label) <-
+        CheckedTags(s, n, t1, label),
+        !NotCheckedLabel(s, n, t1, label);
+
+    Leak(s, n, t1, source, t2) <- // Check failed, so report each tag that is 'more private'.
+        Check(n, t1),
+        CheckedLabel(s, n, t1, label),
+        !AtMostAsPrivate(label, t1),
+        CheckedTag(s, n, source, t2),
+        AppliesTo(t1, t2),
+        !AtMostAsPrivate(t2, t1);
+
+    // A particle's control inputs (e.g. a user action) decide which of its outputs are written, so
+    // their tags leak to every output through control flow, even when no data flows between them.
+    // These implicit tags propagate like other tags, and are reported separately.
+    struct ControlFlow(Ent, Ent); // control input, output
+    ControlFlow(control, out) <-
+        ControlInput(control),
+        Node(particle, control, _),
+        Node(particle, out, out_ty),
+        (control != out),
+        HasCapability(out_cap, out_ty),
+        Capability(out_cap, _); // Is output (e.g. write)
+
+    struct HasImplicitTag(Sol, Ent, Ent, Ent); // solution, source node, node with tag, tag
+    HasImplicitTag(s, source, out, tag) <-
+        HasTag(s, source, control, tag),
+        ControlFlow(control, out),
+        !TrustedToRemoveTag(out, tag),
+        !TrustedToRemoveTagFromNode(out, control),
+        !Declassifies(control, out, tag);
+    HasImplicitTag(s, source, out, tag) <-
+        HasFieldTag(s, source, control, _, tag),
+        ControlFlow(control, out),
+        !TrustedToRemoveTag(out, tag),
+        !TrustedToRemoveTagFromNode(out, control),
+        !Declassifies(control, out, tag);
+    HasImplicitTag(s, source, *down, tag) <- // Propagate tags 'downstream'
+        HasImplicitTag(s, source, curr, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        !TrustedToRemoveTag(*down, tag),
+        !TrustedToRemoveTagFromNode(*down, curr),
+        !Declassifies(curr, *down, tag);
+    HasImplicitTag(s, source, down, tag) <- // Propagate tags 'across stream' (i.e. inside a particle)
+        HasImplicitTag(s, source, curr, tag),
+        ParticleFlow(curr, down),
+        !TrustedToRemoveTag(down, tag),
+        !TrustedToRemoveTagFromNode(down, curr),
+        !Declassifies(curr, down, tag);
+    HasImplicitTag(s, source, out, tag) <-
+        HasImplicitTag(s, source, control, tag),
+        ControlFlow(control, out),
+        !TrustedToRemoveTag(out, tag),
+        !TrustedToRemoveTagFromNode(out, control),
+        !Declassifies(control, out, tag);
+
+    // Policies (see policy.rs) are checked against each tag reaching a node, with its source.
+    // Negations have been pushed down to the tags and particles, so only those rules need them.
+    struct SubPolicy(Ent, Ent); // policy, part of the policy
+    SubPolicy(p, p) <- PolicyCheck(_, p);
+    SubPolicy(p, p) <- CategoryPolicy(_, p);
+    SubPolicy(root, a) <- SubPolicy(root, p), PolicyAnd(p, a, _);
+    SubPolicy(root, b) <- SubPolicy(root, p), PolicyAnd(p, _, b);
+    SubPolicy(root, a) <- SubPolicy(root, p), PolicyOr(p, a, _);
+    SubPolicy(root, b) <- SubPolicy(root, p), PolicyOr(p, _, b);
+
+    struct PolicyTag(Ent, Ent, Ent); // policy, source, tag
+    PolicyTag(p, source, tag) <- PolicyCheck(n, root), SubPolicy(root, p), HasTag(_, source, n, tag);
+    PolicyTag(p, source, tag) <-
+        PolicyCheck(n, root),
+        SubPolicy(root, p),
+        HasFieldTag(_, source, n, _, tag);
+    PolicyTag(p, source, tag) <-
+        PolicyCheck(n, root),
+        SubPolicy(root, p),
+        HasImplicitTag(_, source, n, tag);
+
+    PolicyTag(p, source, tag) <-
+        CategoryPolicy(category, root),
+        SinkCategory(n, category),
+        SubPolicy(root, p),
+        HasTag(_, source, n, tag);
+    PolicyTag(p, source, tag) <-
+        CategoryPolicy(category, root),
+        SinkCategory(n, category),
+        SubPolicy(root, p),
+        HasFieldTag(_, source, n, _, tag);
+
+    struct ParticleNode(Ent, Ent); // particle, node
+    ParticleNode(particle, node) <- Node(particle, node, _);
+
+    struct PolicyHolds(Ent, Ent, Ent); // policy, source, tag
+    PolicyHolds(p, source, tag) <- PolicyTag(p, source, tag), PolicyWithin(p, bound), (tag == bound);
+    PolicyHolds(p, source, tag) <-
+        PolicyTag(p, source, tag),
+        PolicyWithin(p, bound),
+        LessPrivate(tag, bound);
+    PolicyHolds(p, source, tag) <-
+        PolicyTag(p, source, tag),
+        PolicyNotWithin(p, bound),
+        (tag != bound),
+        !LessPrivate(tag, bound);
+    PolicyHolds(p, source, tag) <-
+        PolicyTag(p, source, tag),
+        PolicyFrom(p, particle),
+        ParticleNode(particle, source);
+    PolicyHolds(p, source, tag) <-
+        PolicyTag(p, source, tag),
+        PolicyNotFrom(p, particle),
+        !ParticleNode(particle, source);
+    PolicyHolds(p, source, tag) <-
+        PolicyAnd(p, a, b),
+        PolicyHolds(a, source, tag),
+        PolicyHolds(b, source, tag);
+    PolicyHolds(p, source, tag) <- PolicyOr(p, a, _), PolicyHolds(a, source, tag);
+    PolicyHolds(p, source, tag) <- PolicyOr(p, _, b), PolicyHolds(b, source, tag);
+
+    Leak(s, n, policy, source, tag) <-
+        PolicyCheck(n, policy),
+        HasTag(s, source, n, tag),
+        !PolicyHolds(policy, source, tag);
+    Leak(s, n, policy, source, tag) <-
+        PolicyCheck(n, policy),
+        HasFieldTag(s, source, n, _, tag),
+        !PolicyHolds(policy, source, tag);
+
+    // Sinks (e.g. network or disk egress points) are categorized by the particles they are inputs
+    // of, or by their types, so that checks and policies can apply to every sink in a category.
+    struct SinkCategory(Ent, Ent); // node, category
+    SinkCategory(node, category) <-
+        ParticleCategory(particle, category),
+        Node(particle, node, ty),
+        HasCapability(cap, ty),
+        Capability(_, cap); // Is input (e.g. read)
+    SinkCategory(node, category) <-
+        TypeCategory(category_ty, category),
+        Node(_, node, ty),
+        DataType(ty, data),
+        Subtype(data, category_ty);
+
+    CategoryLeak(s, category, n, t1, source, t2) <-
+        CategoryCheck(category, t1),
+        SinkCategory(n, category),
+        HasTag(s, source, n, t2),
+        AppliesTo(t1, t2),
+        !AtMostAsPrivate(t2, t1);
+    CategoryLeak(s, category, n, t1, source, t2) <-
+        CategoryCheck(category, t1),
+        SinkCategory(n, category),
+        HasFieldTag(s, source, n, _, t2),
+        AppliesTo(t1, t2),
+        !AtMostAsPrivate(t2, t1);
+    CategoryLeak(s, category, n, policy, source, tag) <-
+        CategoryPolicy(category, policy),
+        SinkCategory(n, category),
+        HasTag(s, source, n, tag),
+        !PolicyHolds(policy, source, tag);
+    CategoryLeak(s, category, n, policy, source, tag) <-
+        CategoryPolicy(category, policy),
+        SinkCategory(n, category),
+        HasFieldTag(s, source, n, _, tag),
+        !PolicyHolds(policy, source, tag);
+
+    // Only implicit flows that are not already explicit leaks are reported.
+    ImplicitFlow(s, n, t1, source, t2) <-
+        Check(n, t1),
+        HasImplicitTag(s, source, n, t2),
+        AppliesTo(t1, t2),
+        !AtMostAsPrivate(t2, t1),
+        !Leak(s, n, t1, source, t2);
+    ImplicitFlow(s, n, policy, source, tag) <-
+        PolicyCheck(n, policy),
+        HasImplicitTag(s, source, n, tag),
+        !PolicyHolds(policy, source, tag),
+        !Leak(s, n, policy, source, tag);
+
+    // Integrity tags (e.g. 'untrusted') describe where data came from, and propagate like privacy
+    // tags, but are checked with the opposite monotonicity: a check requires data to be at least
+    // as trusted as its tag, so data with a 'less trusted' tag violates it.
+    HasIntegrityTag(s, n, n, tag) <- UncheckedSolution(s), IntegrityClaim(n, tag);
+    HasIntegrityTag(s, source, *down, tag) <- // Propagate tags 'downstream'
+        HasIntegrityTag(s, source, curr, tag),
+        for (up, down) in &s.solution().edges,
+        (*up == curr),
+        !TrustedToEndorse(*down, tag);
+
+    HasIntegrityTag(s, source, down, tag) <- // Propagate tags 'across stream' (i.e. inside a particle)
+        HasIntegrityTag(s, source, curr, tag),
+        ParticleFlow(curr, down),
+        !TrustedToEndorse(down, tag);
+
+    struct LessTrusted(Ent, Ent); // The transitive closure of LessTrustedThan
+    LessTrusted(t1, t2) <- LessTrustedThan(t1, t2);
+    LessTrusted(t1, t3) <- LessTrusted(t1, t2), LessTrusted(t2, t3);
+
+    IntegrityViolation(s, n, required, source, tag) <-
+        IntegrityCheck(n, required),
+        LessTrusted(tag, required),
+        HasIntegrityTag(s, source, n, tag); // Check failed, node has a 'less trusted' tag.
 
     TypeError(s, *from, from_ty, *to, to_ty) <-
         UncheckedSolution(s),
@@ -229,10 +819,20 @@ crepe! {
         Node(_to_p, *to, to_ty),
         !CompatibleWith(from_ty, to_ty); // Check failed, from writes an incompatible type into to
 
+    EdgeCapability(s, *from, *to, from_cap, to_cap) <-
+        UncheckedSolution(s),
+        for (from, to) in &s.solution().edges,
+        Node(_from_p, *from, from_ty),
+        Node(_to_p, *to, to_ty),
+        CapabilityJustifies(from_ty, to_ty, from_cap, to_cap);
+
     Solution(s) <-
         UncheckedSolution(s),
         !TypeError(s, _, _, _, _),
-        !Leak(s, _, _, _, _);
+        !Leak(s, _, _, _, _),
+        !ImplicitFlow(s, _, _, _, _),
+        !CategoryLeak(s, _, _, _, _, _),
+        !IntegrityViolation(s, _, _, _, _);
 
     KnownType(name!(ty)) <- KnownType(ty); // Types without their arguments are still types
     KnownType(arg) <- KnownType(ty), for arg in ty.args(); // Types arguments are types
@@ -248,10 +848,20 @@ fn is_default<T: Default + Eq>(v: &T) -> bool {
     v == &T::default()
 }
 
+// Solution ids are only meaningful to the process that found the solution, so ids in the input are
+// accepted but ignored. They are regenerated from the edges when the recipe is solved again.
+fn ignore_id<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Sol>, D::Error> {
+    serde::de::IgnoredAny::deserialize(deserializer)?;
+    Ok(None)
+}
+
 const PLANNING: &str = "planning";
 const D3_OUTPUT: &str = "d3";
 const DOT_OUTPUT: &str = "dot";
-const FLAGS: &[&str] = &[PLANNING, D3_OUTPUT, DOT_OUTPUT];
+const LINT: &str = "lint";
+pub const PUBLIC: &str = "ibis.Public"; // The least private tag.
+pub const PRIVATE: &str = "ibis.Private"; // The most private tag.
+const FLAGS: &[&str] = &[PLANNING, D3_OUTPUT, DOT_OUTPUT, LINT];
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
@@ -263,8 +873,22 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "is_default")]
     pub less_private_than: Vec<LessPrivateThan>,
     #[serde(default, skip_serializing_if = "is_default")]
+    pub less_trusted_than: Vec<LessTrustedThan>,
+    #[serde(default, skip_serializing_if = "is_default")]
     pub capabilities: Vec<Capability>,
     #[serde(default, skip_serializing_if = "is_default")]
+    pub capability_implies: Vec<CapabilityImplies>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub json_schemas: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub particle_categories: Vec<ParticleCategory>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub type_categories: Vec<TypeCategory>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub category_checks: Vec<CategoryCheck>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub category_policies: Vec<(Ent, String)>,
+    #[serde(default, skip_serializing_if = "is_default")]
     pub flags: BTreeMap<String, bool>,
 }
 
@@ -277,6 +901,67 @@ pub struct Feedback {
     pub type_errors: Vec<TypeError>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub has_tags: Vec<HasTag>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub has_field_tags: Vec<HasFieldTag>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub edge_capabilities: Vec<EdgeCapability>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub privacy_labels: Vec<PrivacyLabel>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub integrity_violations: Vec<IntegrityViolation>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub has_integrity_tags: Vec<HasIntegrityTag>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub declassifications: Vec<Declassification>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub implicit_flows: Vec<ImplicitFlow>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub category_leaks: Vec<CategoryLeak>,
+}
+
+// Feedback is sorted by its entities, all of which (other than the solution) are named.
+macro_rules! names_of_fields {
+    ($($relation: ident($($field: tt),*)),* $(,)?) => {
+        $(
+            impl Names for $relation {
+                fn add_names(&self, names: &mut Vec<String>) {
+                    $(self.$field.add_names(names);)*
+                }
+            }
+        )*
+    };
+}
+
+names_of_fields!(
+    Node(0, 1, 2),
+    Leak(1, 2, 3, 4),
+    TypeError(1, 2, 3, 4),
+    HasTag(1, 2, 3),
+    HasFieldTag(1, 2, 3, 4),
+    EdgeCapability(1, 2, 3, 4),
+    PrivacyLabel(1, 2),
+    IntegrityViolation(1, 2, 3, 4),
+    HasIntegrityTag(1, 2, 3),
+    Declassification(1, 2, 3),
+    ImplicitFlow(1, 2, 3, 4),
+    CategoryLeak(1, 2, 3, 4, 5),
+);
+
+impl Feedback {
+    // Orders the feedback by name, as crepe's output order is not stable between runs.
+    fn sort(&mut self) {
+        sort_by_name(&mut self.leaks);
+        sort_by_name(&mut self.type_errors);
+        sort_by_name(&mut self.has_tags);
+        sort_by_name(&mut self.has_field_tags);
+        sort_by_name(&mut self.edge_capabilities);
+        sort_by_name(&mut self.privacy_labels);
+        sort_by_name(&mut self.integrity_violations);
+        sort_by_name(&mut self.has_integrity_tags);
+        sort_by_name(&mut self.declassifications);
+        sort_by_name(&mut self.implicit_flows);
+        sort_by_name(&mut self.category_leaks);
+    }
 }
 
 fn starting_recipes() -> Vec<Recipe> {
@@ -302,6 +987,8 @@ pub struct Ibis {
     pub d3_output: Option<crate::d3::D3Graph>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub dot_output: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub leak_reports: Vec<LeakReport>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -311,7 +998,11 @@ pub struct Recipe {
     pub metadata: serde_json::Value,
     #[serde(default, skip_serializing_if = "is_default")]
     pub warnings: Vec<String>,
-    #[serde(skip, default)]
+    #[serde(
+        default,
+        deserialize_with = "ignore_id",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub id: Option<Sol>,
     // Do not deserialize the feedback on a recipe: Re-generate it each time for consistency.
     #[serde(flatten)]
@@ -327,15 +1018,45 @@ pub struct Recipe {
     #[serde(default, skip_serializing_if = "is_default")]
     pub trusted_to_remove_tag_from_node: Vec<TrustedToRemoveTagFromNode>,
     #[serde(default, skip_serializing_if = "is_default")]
+    pub internal_flows: Vec<InternalFlow>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub no_internal_flow: Vec<NoInternalFlow>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub control_inputs: Vec<ControlInput>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub policies: Vec<(Ent, String)>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub trusted_to_remove_tag_into: Vec<TrustedToRemoveTagInto>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub trusted_to_remove_tag_for_purpose: Vec<TrustedToRemoveTagForPurpose>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub purposes: Vec<Purpose>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub integrity_claims: Vec<IntegrityClaim>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub integrity_checks: Vec<IntegrityCheck>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub trusted_to_endorse: Vec<TrustedToEndorse>,
+    #[serde(default, skip_serializing_if = "is_default")]
     pub edges: Vec<(Ent, Ent)>,
     #[cfg(feature = "ancestors")]
     #[serde(default, skip_serializing_if = "is_default")]
     pub ancestors: Vec<Sol>,
 }
 
+// Recipes are ordered by their edges, then their nodes.
+impl Names for Recipe {
+    fn add_names(&self, names: &mut Vec<String>) {
+        self.edges.add_names(names);
+        self.nodes.add_names(names);
+    }
+}
+
 impl Recipe {
     pub fn from_sol(sol: Sol) -> Self {
         let solution = sol.solution();
+        let mut edges: Vec<(Ent, Ent)> = solution.edges.iter().cloned().collect();
+        sort_by_name(&mut edges);
         Recipe {
             #[cfg(feature = "ancestors")]
             ancestors: sol.ancestors().iter().cloned().collect(),
@@ -348,11 +1069,22 @@ impl Recipe {
             checks: vec![],
             trusted_to_remove_tag: vec![],
             trusted_to_remove_tag_from_node: vec![],
-            edges: solution.edges.iter().cloned().collect(),
+            internal_flows: vec![],
+            no_internal_flow: vec![],
+            control_inputs: vec![],
+            policies: vec![],
+            trusted_to_remove_tag_into: vec![],
+            trusted_to_remove_tag_for_purpose: vec![],
+            purposes: vec![],
+            integrity_claims: vec![],
+            integrity_checks: vec![],
+            trusted_to_endorse: vec![],
+            edges,
         }
     }
 
-    pub fn with_feedback(mut self, feedback: Feedback) -> Self {
+    pub fn with_feedback(mut self, mut feedback: Feedback) -> Self {
+        feedback.sort();
         self.feedback = feedback;
         self
     }
@@ -373,6 +1105,17 @@ impl From<Sol> for Recipe {
     }
 }
 
+fn extend_with_policies(runtime: &mut Crepe, facts: PolicyFacts) {
+    runtime.extend(facts.checks);
+    runtime.extend(facts.category_checks);
+    runtime.extend(facts.within);
+    runtime.extend(facts.not_within);
+    runtime.extend(facts.from);
+    runtime.extend(facts.not_from);
+    runtime.extend(facts.and);
+    runtime.extend(facts.or);
+}
+
 impl Ibis {
     pub fn new() -> Self {
         Ibis::default() // All the accumulated recipe info
@@ -385,7 +1128,14 @@ impl Ibis {
                     metadata: _,
                     subtypes,
                     less_private_than,
+                    less_trusted_than,
                     capabilities,
+                    capability_implies,
+                    json_schemas,
+                    particle_categories,
+                    type_categories,
+                    category_checks,
+                    category_policies,
                     flags,
                 },
             mut recipes, // Mutation required to move rather than copy the data.
@@ -395,11 +1145,19 @@ impl Ibis {
             num_selected: _,
             d3_output: _,
             dot_output: _,
+            leak_reports: _,
         } = recipes;
         self.config.flags = flags; // TODO: Merge not overwrite.
         self.config.subtypes.extend(subtypes);
         self.config.less_private_than.extend(less_private_than);
+        self.config.less_trusted_than.extend(less_trusted_than);
         self.config.capabilities.extend(capabilities);
+        self.config.capability_implies.extend(capability_implies);
+        self.config.json_schemas.extend(json_schemas);
+        self.config.particle_categories.extend(particle_categories);
+        self.config.type_categories.extend(type_categories);
+        self.config.category_checks.extend(category_checks);
+        self.config.category_policies.extend(category_policies);
         self.recipes.extend(recipes.drain(0..));
         self.shared = shared; // TODO: Merge not overwrite.
     }
@@ -419,9 +1177,44 @@ impl Ibis {
                 ));
             }
         }
+        if let Some(true) = self.config.flags.get(LINT) {
+            warnings.extend(
+                self.config
+                    .lint(self.recipes.iter().chain(Some(&self.shared))),
+            );
+        }
         runtime.extend(self.config.subtypes.clone());
         runtime.extend(self.config.less_private_than.clone());
+        runtime.extend(self.config.less_trusted_than.clone());
         runtime.extend(self.config.capabilities.clone());
+        runtime.extend(self.config.capability_implies.clone());
+        runtime.extend(self.config.particle_categories.clone());
+        runtime.extend(self.config.type_categories.clone());
+        runtime.extend(self.config.category_checks.clone());
+        let mut facts = PolicyFacts::default();
+        for (category, text) in &self.config.category_policies {
+            match Policy::parse(text) {
+                Ok(policy) => policy.compile_category_check(*category, &mut facts),
+                Err(message) => warnings.push(format!("{} on category '{}'", message, category)),
+            }
+        }
+        extend_with_policies(&mut runtime, facts);
+        for (name, schema) in &self.config.json_schemas {
+            // Each schema is equivalent to the named type that handles use to refer to it.
+            let imported = import_json_schema(schema);
+            warnings.extend(
+                imported
+                    .warnings
+                    .iter()
+                    .map(|warning| format!("{} in schema '{}'", warning, name)),
+            );
+            let named = ent!(name);
+            runtime.extend(imported.subtypes);
+            runtime.extend([
+                SubtypeInput(named, imported.ty),
+                SubtypeInput(imported.ty, named),
+            ]);
+        }
 
         let maybe_shared: Option<&Recipe> = if Sol::from(&self.shared) == Sol::default() {
             None
@@ -442,6 +1235,16 @@ impl Ibis {
                 nodes,
                 trusted_to_remove_tag,
                 trusted_to_remove_tag_from_node,
+                internal_flows,
+                no_internal_flow,
+                control_inputs,
+                policies,
+                trusted_to_remove_tag_into,
+                trusted_to_remove_tag_for_purpose,
+                purposes,
+                integrity_claims,
+                integrity_checks,
+                trusted_to_endorse,
                 feedback: _,
                 metadata: _,
                 id: _,
@@ -455,9 +1258,50 @@ impl Ibis {
             runtime.extend(nodes);
             runtime.extend(trusted_to_remove_tag);
             runtime.extend(trusted_to_remove_tag_from_node);
+            runtime.extend(internal_flows);
+            runtime.extend(no_internal_flow);
+            runtime.extend(control_inputs);
+            let mut facts = PolicyFacts::default();
+            for (node, text) in policies {
+                match Policy::parse(text) {
+                    Ok(policy) => policy.compile_check(*node, &mut facts),
+                    Err(message) => warnings.push(format!("{} on node '{}'", message, node)),
+                }
+            }
+            extend_with_policies(&mut runtime, facts);
+            runtime.extend(trusted_to_remove_tag_into);
+            runtime.extend(trusted_to_remove_tag_for_purpose);
+            runtime.extend(purposes);
+            runtime.extend(integrity_claims);
+            runtime.extend(integrity_checks);
+            runtime.extend(trusted_to_endorse);
         }
 
-        let (solutions, unchecked_solutions, has_tags, leaks, type_errors) = runtime.run();
+        let (
+            solutions,
+            unchecked_solutions,
+            has_tags,
+            leaks,
+            type_errors,
+            edge_capabilities,
+            undeclared_capabilities,
+            privacy_labels,
+            has_integrity_tags,
+            has_field_tags,
+            integrity_violations,
+            declassifications,
+            implicit_flows,
+            category_leaks,
+            tag_steps,
+        ) = runtime.run();
+        let mut undeclared_capabilities: Vec<String> = undeclared_capabilities
+            .iter()
+            .map(|UndeclaredCapability(node, cap)| {
+                format!("Node '{}' uses the undeclared capability '{}'", node, cap)
+            })
+            .collect();
+        undeclared_capabilities.sort();
+        warnings.extend(undeclared_capabilities);
         let recipes: Vec<Sol> = if let Some(true) = &self.config.flags.get(PLANNING) {
             solutions.iter().map(|Solution(s)| *s).collect()
         } else {
@@ -466,29 +1310,35 @@ impl Ibis {
                 .map(|UncheckedSolution(s)| *s)
                 .collect()
         };
+        // Group the feedback by solution in one pass, rather than filtering it for each solution.
+        let mut feedback: BTreeMap<Sol, Feedback> = BTreeMap::new();
+        macro_rules! group_by_solution {
+            ($($field: ident),*) => {
+                $(
+                    for fact in &$field {
+                        feedback.entry(fact.0).or_default().$field.push(fact.clone());
+                    }
+                )*
+            };
+        }
+        group_by_solution!(
+            leaks,
+            type_errors,
+            has_tags,
+            has_field_tags,
+            edge_capabilities,
+            privacy_labels,
+            integrity_violations,
+            has_integrity_tags,
+            declassifications,
+            implicit_flows,
+            category_leaks
+        );
         let mut recipes: Vec<Recipe> = recipes
             .iter()
-            .map(|s| {
-                Recipe::from_sol(*s).with_feedback(Feedback {
-                    leaks: leaks
-                        .iter()
-                        .filter(|Leak(leak_s, _, _, _, _)| leak_s == s)
-                        .cloned()
-                        .collect(),
-                    type_errors: type_errors
-                        .iter()
-                        .filter(|TypeError(type_s, _, _, _, _)| type_s == s)
-                        .cloned()
-                        .collect(),
-                    has_tags: has_tags
-                        .iter()
-                        .filter(|HasTag(has_tag_s, _, _, _)| has_tag_s == s)
-                        .cloned()
-                        .collect(),
-                })
-            })
+            .map(|s| Recipe::from_sol(*s).with_feedback(feedback.remove(s).unwrap_or_default()))
             .collect();
-        let recipes = if let Some(loss) = loss {
+        let mut recipes = if let Some(loss) = loss {
             let mut max = 0;
             for r in &recipes {
                 let l = r.edges.len();
@@ -503,6 +1353,8 @@ impl Ibis {
         } else {
             recipes
         };
+        sort_by_name(&mut recipes);
+        let leak_reports = leak_reports(&recipes, &tag_steps);
         let mut shared = self.shared;
         shared.warnings.extend(warnings);
         for recipe in self.recipes.drain(0..) {
@@ -515,6 +1367,20 @@ impl Ibis {
             shared
                 .trusted_to_remove_tag_from_node
                 .extend(recipe.trusted_to_remove_tag_from_node);
+            shared.internal_flows.extend(recipe.internal_flows);
+            shared.no_internal_flow.extend(recipe.no_internal_flow);
+            shared.control_inputs.extend(recipe.control_inputs);
+            shared.policies.extend(recipe.policies);
+            shared
+                .trusted_to_remove_tag_into
+                .extend(recipe.trusted_to_remove_tag_into);
+            shared
+                .trusted_to_remove_tag_for_purpose
+                .extend(recipe.trusted_to_remove_tag_for_purpose);
+            shared.purposes.extend(recipe.purposes);
+            shared.integrity_claims.extend(recipe.integrity_claims);
+            shared.integrity_checks.extend(recipe.integrity_checks);
+            shared.trusted_to_endorse.extend(recipe.trusted_to_endorse);
         }
 
         let mut result = Ibis {
@@ -524,6 +1390,7 @@ impl Ibis {
             num_selected: recipes.len(),
             recipes,
             shared,
+            leak_reports,
             // '_output's are unused unless requested
             d3_output: None,
             dot_output: None,
@@ -0,0 +1,244 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::type_struct::{Type, INT, STRING};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subject {
+    Value,  // x
+    Length, // len
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    Compare(Subject, Comparison, i64),
+    Matches(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    // The comparison with its arguments swapped (e.g. 'a < b' is 'b > a').
+    pub fn flip(self) -> Self {
+        match self {
+            Comparison::Less => Comparison::Greater,
+            Comparison::LessOrEqual => Comparison::GreaterOrEqual,
+            Comparison::Equal => Comparison::Equal,
+            Comparison::GreaterOrEqual => Comparison::LessOrEqual,
+            Comparison::Greater => Comparison::Less,
+        }
+    }
+}
+
+// An inclusive range of integers, unbounded where either end is None.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bounds {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl Bounds {
+    pub fn is_unbounded(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max)
+    }
+
+    // Whether every value in self is also in other.
+    pub fn within(&self, other: &Bounds) -> bool {
+        let min_ok = match (self.min, other.min) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(min), Some(other_min)) => other_min <= min,
+        };
+        let max_ok = match (self.max, other.max) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(max), Some(other_max)) => max <= other_max,
+        };
+        min_ok && max_ok
+    }
+
+    fn restrict_min(&mut self, min: i64) {
+        self.min = Some(self.min.map_or(min, |current| current.max(min)));
+    }
+
+    fn restrict_max(&mut self, max: i64) {
+        self.max = Some(self.max.map_or(max, |current| current.min(max)));
+    }
+
+    pub fn constrain(&mut self, comparison: Comparison, value: i64) {
+        match comparison {
+            Comparison::Less => self.restrict_max(value.saturating_sub(1)),
+            Comparison::LessOrEqual => self.restrict_max(value),
+            Comparison::Equal => {
+                self.restrict_min(value);
+                self.restrict_max(value);
+            }
+            Comparison::GreaterOrEqual => self.restrict_min(value),
+            Comparison::Greater => self.restrict_min(value.saturating_add(1)),
+        }
+    }
+
+    fn fmt_with_subject(&self, f: &mut std::fmt::Formatter<'_>, subject: &str) -> std::fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{} == {}", subject, min),
+            (Some(min), Some(max)) => write!(f, "{} <= {} <= {}", min, subject, max),
+            (Some(min), None) => write!(f, "{} >= {}", subject, min),
+            (None, Some(max)) => write!(f, "{} <= {}", subject, max),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+// A conjunction of decidable predicates over the values of a type.
+// Refinements are kept in a normal form (e.g. 'x < 100' is stored as 'x <= 99') so that
+// equivalent refinements are equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Refinement {
+    pub value: Bounds,
+    pub length: Bounds,
+    pub patterns: BTreeSet<String>,
+}
+
+impl Refinement {
+    pub fn add(&mut self, constraint: Constraint) {
+        match constraint {
+            Constraint::Compare(subject, comparison, value) => {
+                self.constrain(subject, comparison, value)
+            }
+            Constraint::Matches(pattern) => self.add_pattern(&pattern),
+        }
+    }
+
+    pub fn constrain(&mut self, subject: Subject, comparison: Comparison, value: i64) {
+        match subject {
+            Subject::Value => self.value.constrain(comparison, value),
+            Subject::Length => self.length.constrain(comparison, value),
+        }
+    }
+
+    pub fn add_pattern(&mut self, pattern: &str) {
+        self.patterns.insert(pattern.to_string());
+    }
+
+    // Whether every value satisfying self also satisfies other.
+    // Patterns are compared syntactically, so this may miss some implications, but is never
+    // wrong.
+    pub fn implies(&self, other: &Refinement) -> bool {
+        self.value.within(&other.value)
+            && self.length.within(&other.length)
+            && other.patterns.is_subset(&self.patterns)
+    }
+
+    // Whether the literal type (e.g. 42 or "small") satisfies this refinement.
+    pub fn accepts_literal(&self, literal: &Type) -> bool {
+        if !self.patterns.is_empty() {
+            return false; // Patterns cannot be checked without evaluating them.
+        }
+        match literal.literal_base() {
+            Some(base) if base == INT => {
+                self.length.is_unbounded()
+                    && literal
+                        .name
+                        .parse::<i64>()
+                        .is_ok_and(|value| self.value.contains(value))
+            }
+            Some(base) if base == STRING => {
+                let length = literal.name.chars().count() as i64 - 2; // Ignore the quotes.
+                self.value.is_unbounded() && self.length.contains(length)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Refinement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        let mut separator = |f: &mut std::fmt::Formatter<'_>| {
+            if first {
+                first = false;
+                Ok(())
+            } else {
+                write!(f, " and ")
+            }
+        };
+        if !self.value.is_unbounded() {
+            separator(f)?;
+            self.value.fmt_with_subject(f, "x")?;
+        }
+        if !self.length.is_unbounded() {
+            separator(f)?;
+            self.length.fmt_with_subject(f, "len")?;
+        }
+        for pattern in &self.patterns {
+            separator(f)?;
+            write!(f, "x matches /{}/", pattern)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: Option<i64>, max: Option<i64>) -> Refinement {
+        Refinement {
+            value: Bounds { min, max },
+            ..Refinement::default()
+        }
+    }
+
+    #[test]
+    fn strict_comparisons_are_normalized() {
+        let mut refinement = Refinement::default();
+        refinement.constrain(Subject::Value, Comparison::Less, 100);
+        refinement.constrain(Subject::Value, Comparison::Greater, -1);
+        assert_eq!(refinement, range(Some(0), Some(99)));
+    }
+
+    #[test]
+    fn narrower_ranges_imply_wider_ranges() {
+        assert!(range(None, Some(9)).implies(&range(None, Some(99))));
+        assert!(!range(None, Some(99)).implies(&range(None, Some(9))));
+        assert!(range(Some(0), Some(9)).implies(&range(None, Some(9))));
+        assert!(!range(None, Some(9)).implies(&range(Some(0), Some(9))));
+    }
+
+    #[test]
+    fn more_patterns_imply_fewer_patterns() {
+        let mut both = Refinement::default();
+        both.add_pattern("[a-z]+");
+        both.add_pattern(".*@.*");
+        let mut one = Refinement::default();
+        one.add_pattern("[a-z]+");
+        assert!(both.implies(&one));
+        assert!(!one.implies(&both));
+    }
+
+    #[test]
+    fn literals_are_checked_against_refinements() {
+        let small = range(Some(0), Some(9));
+        assert!(small.accepts_literal(&Type::new("5")));
+        assert!(!small.accepts_literal(&Type::new("10")));
+        let mut short = Refinement::default();
+        short.constrain(Subject::Length, Comparison::LessOrEqual, 3);
+        assert!(short.accepts_literal(&Type::new("\"abc\"")));
+        assert!(!short.accepts_literal(&Type::new("\"abcd\"")));
+    }
+}
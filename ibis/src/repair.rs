@@ -0,0 +1,286 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::leak_report::checked_node;
+use crate::recipes::{
+    CategoryLeak, Config, Ibis, ImplicitFlow, IntegrityViolation, Leak, Node, Recipe, SubtypeInput,
+    TrustedToRemoveTag, TypeError,
+};
+use crate::type_struct::WITH_CAPABILITY;
+use crate::{Ent, Sol};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// A change to a recipe (or its configuration) that would fix some of its violations.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum Repair {
+    RemoveEdge(Ent, Ent),
+    // Replaces the edge from -> to with from -> input and output -> to, where the declassifier's
+    // output is trusted to remove a leaked tag.
+    InsertDeclassifier {
+        from: Ent,
+        to: Ent,
+        input: Ent,
+        output: Ent,
+    },
+    AddSubtype(Ent, Ent),
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::RemoveEdge(from, to) => write!(f, "Remove the edge {} -> {}", from, to),
+            Repair::InsertDeclassifier {
+                from,
+                to,
+                input,
+                output,
+            } => write!(
+                f,
+                "Replace the edge {} -> {} with {} -> {} and {} -> {}",
+                from, to, from, input, output, to
+            ),
+            Repair::AddSubtype(sub, sup) => write!(f, "Declare '{}' a subtype of '{}'", sub, sup),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RepairSuggestion {
+    pub repair: Repair,
+    // The number of the recipe's violations (e.g. leaks and type errors) that the repair fixes.
+    pub fixes: usize,
+}
+
+// The feedback that a repair should fix, without the solution (which the repair changes).
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum Violation {
+    Leak(Ent, Ent, Ent, Ent),
+    TypeError(Ent, Ent, Ent, Ent),
+    Integrity(Ent, Ent, Ent, Ent),
+    ImplicitFlow(Ent, Ent, Ent, Ent),
+    CategoryLeak(Ent, Ent, Ent, Ent, Ent),
+}
+
+fn violations(recipe: &Recipe) -> BTreeSet<Violation> {
+    let feedback = &recipe.feedback;
+    let leaks = feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, expected, source, tag)| {
+            Violation::Leak(*node, *expected, *source, *tag)
+        });
+    let type_errors = feedback
+        .type_errors
+        .iter()
+        .map(|TypeError(_, from, from_ty, to, to_ty)| {
+            Violation::TypeError(*from, *from_ty, *to, *to_ty)
+        });
+    let integrity_violations = feedback.integrity_violations.iter().map(
+        |IntegrityViolation(_, node, required, source, tag)| {
+            Violation::Integrity(*node, *required, *source, *tag)
+        },
+    );
+    let implicit_flows =
+        feedback
+            .implicit_flows
+            .iter()
+            .map(|ImplicitFlow(_, node, expected, source, tag)| {
+                Violation::ImplicitFlow(*node, *expected, *source, *tag)
+            });
+    let category_leaks = feedback.category_leaks.iter().map(
+        |CategoryLeak(_, category, node, expected, source, tag)| {
+            Violation::CategoryLeak(*category, *node, *expected, *source, *tag)
+        },
+    );
+    leaks
+        .chain(type_errors)
+        .chain(integrity_violations)
+        .chain(implicit_flows)
+        .chain(category_leaks)
+        .collect()
+}
+
+// The tags that reached a node they should not have, with their sources: (source, node, tag).
+fn leaked_tags(recipe: &Recipe) -> BTreeSet<(Ent, Ent, Ent)> {
+    let feedback = &recipe.feedback;
+    let leaks = feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, _, source, tag)| (*source, checked_node(*node), *tag));
+    let implicit_flows = feedback
+        .implicit_flows
+        .iter()
+        .map(|ImplicitFlow(_, node, _, source, tag)| (*source, checked_node(*node), *tag));
+    let category_leaks = feedback
+        .category_leaks
+        .iter()
+        .map(|CategoryLeak(_, _, node, _, source, tag)| (*source, checked_node(*node), *tag));
+    leaks.chain(implicit_flows).chain(category_leaks).collect()
+}
+
+fn without_capabilities(ty: Ent) -> Ent {
+    if ty.is_a(WITH_CAPABILITY) {
+        without_capabilities(ty.args()[1])
+    } else {
+        ty
+    }
+}
+
+impl Ibis {
+    fn particle_of(&self, node: Ent) -> Option<Ent> {
+        self.shared
+            .nodes
+            .iter()
+            .find(|Node(_, other, _)| *other == node)
+            .map(|Node(particle, _, _)| *particle)
+    }
+
+    // The nodes that data may flow to from a node (including itself), along the recipe's edges
+    // and between the nodes of each particle.
+    fn downstream(&self, recipe: &Recipe, node: Ent) -> BTreeSet<Ent> {
+        let mut reached = BTreeSet::from([node]);
+        let mut todo = vec![node];
+        while let Some(current) = todo.pop() {
+            let particle = self.particle_of(current);
+            let across = self
+                .shared
+                .nodes
+                .iter()
+                .filter(|Node(other, _, _)| Some(*other) == particle)
+                .map(|Node(_, down, _)| *down);
+            let along = recipe
+                .edges
+                .iter()
+                .filter(|(up, _)| *up == current)
+                .map(|(_, down)| *down);
+            for down in across.chain(along) {
+                if reached.insert(down) {
+                    todo.push(down);
+                }
+            }
+        }
+        reached
+    }
+
+    // The edges that a tag took from its source to the node it leaked into.
+    fn leak_path(&self, recipe: &Recipe, (source, node, _): (Ent, Ent, Ent)) -> Vec<(Ent, Ent)> {
+        let from_source = self.downstream(recipe, source);
+        recipe
+            .edges
+            .iter()
+            .filter(|(from, to)| {
+                from_source.contains(from) && self.downstream(recipe, *to).contains(&node)
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn candidate_repairs(&self, recipe: &Recipe) -> BTreeSet<Repair> {
+        let mut repairs: BTreeSet<Repair> = recipe
+            .edges
+            .iter()
+            .map(|(from, to)| Repair::RemoveEdge(*from, *to))
+            .collect();
+        let leaked_tags = leaked_tags(recipe);
+        for TrustedToRemoveTag(output, tag) in &self.shared.trusted_to_remove_tag {
+            // Only the edges that a tag the declassifier removes took to a leak are candidates.
+            let edges: BTreeSet<(Ent, Ent)> = leaked_tags
+                .iter()
+                .filter(|(_, _, leaked)| leaked == tag)
+                .flat_map(|leak| self.leak_path(recipe, *leak))
+                .collect();
+            if edges.is_empty() {
+                continue;
+            }
+            let particle = self.particle_of(*output);
+            for Node(_, input, _) in self
+                .shared
+                .nodes
+                .iter()
+                .filter(|Node(other, input, _)| Some(*other) == particle && input != output)
+            {
+                for (from, to) in &edges {
+                    if self.particle_of(*from) != particle && self.particle_of(*to) != particle {
+                        repairs.insert(Repair::InsertDeclassifier {
+                            from: *from,
+                            to: *to,
+                            input: *input,
+                            output: *output,
+                        });
+                    }
+                }
+            }
+        }
+        for TypeError(_, _, from_ty, _, to_ty) in &recipe.feedback.type_errors {
+            repairs.insert(Repair::AddSubtype(
+                without_capabilities(*from_ty),
+                without_capabilities(*to_ty),
+            ));
+        }
+        repairs
+    }
+
+    fn apply_repair(&self, sol: Sol, repair: &Repair) -> (Config, Sol) {
+        match repair {
+            Repair::RemoveEdge(from, to) => (self.config.clone(), sol.remove_edge(*from, *to)),
+            Repair::InsertDeclassifier {
+                from,
+                to,
+                input,
+                output,
+            } => (
+                self.config.clone(),
+                sol.make_child(&|data| {
+                    data.remove_edge(*from, *to)
+                        .add_edge(*from, *input)
+                        .add_edge(*output, *to)
+                }),
+            ),
+            Repair::AddSubtype(sub, sup) => {
+                let mut config = self.config.clone();
+                config.subtypes.push(SubtypeInput(*sub, *sup));
+                (config, sol)
+            }
+        }
+    }
+
+    // Suggests repairs for a recipe's violations (e.g. leaks and type errors), most effective
+    // first. Only repairs that fix at least one violation, without introducing new ones, are
+    // suggested. Like counterexamples, this requires the recipe data in 'shared'.
+    pub fn suggest_repairs(&self, recipe: &Recipe) -> Vec<RepairSuggestion> {
+        let before = violations(recipe);
+        if before.is_empty() {
+            return vec![];
+        }
+        let sol = Sol::from(recipe);
+        let mut suggestions: Vec<RepairSuggestion> = self
+            .candidate_repairs(recipe)
+            .into_iter()
+            .filter_map(|repair| {
+                let (config, repaired) = self.apply_repair(sol, &repair);
+                let after = violations(&self.check_sol(&config, repaired));
+                if !after.is_subset(&before) {
+                    return None;
+                }
+                let fixes = before.len() - after.len();
+                if fixes == 0 {
+                    None
+                } else {
+                    Some(RepairSuggestion { repair, fixes })
+                }
+            })
+            .collect();
+        suggestions.sort_by_cached_key(|suggestion| {
+            (
+                std::cmp::Reverse(suggestion.fixes),
+                suggestion.repair.to_string(),
+            )
+        });
+        suggestions
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use crate::type_struct::*;
+use crate::Ent;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub const LIST: &str = "List";
+pub const NUMBER: &str = "Number";
+pub const NULL: &str = "Null";
+
+// A summary of every sample seen at a single position in the data.
+#[derive(Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    int: bool,
+    number: bool,
+    string: bool,
+    list: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+#[derive(Default)]
+struct ObjectShape {
+    samples: usize,
+    fields: BTreeMap<String, (usize, Shape)>, // label, (samples containing the field, shape)
+}
+
+// Replaces the characters of a JSON key that cannot appear in a label (e.g. 'first name' becomes
+// 'first_name'), so that the inferred type can be read back. Keys that only differ in those
+// characters share a field.
+pub(crate) fn label_of(key: &str) -> String {
+    let label: String = key
+        .chars()
+        .map(|c| match c {
+            '(' | ')' | '{' | '}' | ',' | ':' | '?' | '"' | '|' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect();
+    let is_literal = label == "true" || label == "false" || label.parse::<i64>().is_ok();
+    if label.is_empty() || is_literal {
+        format!("_{}", label)
+    } else {
+        label
+    }
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(number) if number.is_i64() || number.is_u64() => self.int = true,
+            Value::Number(_) => self.number = true,
+            Value::String(_) => self.string = true,
+            Value::Array(items) => {
+                let list = self.list.get_or_insert_with(Box::default);
+                for item in items {
+                    list.add(item);
+                }
+            }
+            Value::Object(fields) => {
+                let object = self.object.get_or_insert_with(ObjectShape::default);
+                object.samples += 1;
+                for (label, field) in fields {
+                    let (count, shape) = object.fields.entry(label_of(label)).or_default();
+                    *count += 1;
+                    shape.add(field);
+                }
+            }
+        }
+    }
+
+    fn to_type(&self) -> Type {
+        let mut members: Vec<Type> = vec![];
+        if let Some(object) = &self.object {
+            members.push(object.to_type());
+        }
+        if let Some(list) = &self.list {
+            members.push(Type::new(LIST).with_arg(list.to_type()));
+        }
+        if self.boolean {
+            members.push(Type::new(BOOLEAN));
+        }
+        if self.number {
+            members.push(Type::new(NUMBER)); // Integers are widened to numbers.
+        } else if self.int {
+            members.push(Type::new(INT));
+        }
+        if self.string {
+            members.push(Type::new(STRING));
+        }
+        if self.null {
+            members.push(Type::new(NULL));
+        }
+        match members.len() {
+            0 => Type::new(UNIVERSAL), // e.g. the elements of an empty list.
+            1 => members.pop().expect("Members should have a single element"),
+            _ => Type::new(UNION).with_args(members),
+        }
+    }
+}
+
+impl ObjectShape {
+    fn to_type(&self) -> Type {
+        let mut fields: Vec<Type> = self
+            .fields
+            .iter()
+            .map(|(label, (count, shape))| {
+                // Fields that are missing from some samples are optional.
+                let labelled = if *count < self.samples {
+                    OPTIONAL_LABELLED
+                } else {
+                    LABELLED
+                };
+                Type::new(labelled)
+                    .with_arg(Type::new(label))
+                    .with_arg(shape.to_type())
+            })
+            .collect();
+        if fields.len() == 1 {
+            fields.pop().expect("Fields should have a single element")
+        } else {
+            Type::new(PRODUCT).with_args(fields)
+        }
+    }
+}
+
+// Infers a type that all of the samples are instances of.
+pub fn infer_type<'a>(samples: impl IntoIterator<Item = &'a Value>) -> Ent {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+    Ent::by_type(Arc::new(shape.to_type()))
+}
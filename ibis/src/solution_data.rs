@@ -24,4 +24,29 @@ impl SolutionData {
         n.edges.insert((from, to));
         n
     }
+
+    // A hash of the edges' names that does not depend on the order that types were interned in
+    // (64 bit FNV-1a, which unlike the std hashers is stable between releases).
+    pub fn content_hash(&self, name: impl Fn(&Ent) -> String) -> u64 {
+        let mut edges: Vec<(String, String)> = self
+            .edges
+            .iter()
+            .map(|(from, to)| (name(from), name(to)))
+            .collect();
+        edges.sort();
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for (from, to) in &edges {
+            for byte in from.bytes().chain([0]).chain(to.bytes()).chain([0]) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    pub fn remove_edge(&self, from: Ent, to: Ent) -> SolutionData {
+        let mut n = SolutionData { ..self.clone() };
+        n.edges.remove(&(from, to));
+        n
+    }
 }
@@ -14,23 +14,27 @@ use std::borrow::Borrow;
 use std::collections::BTreeSet;
 use std::sync::Arc;
 
-pub type SolutionIdBackingType = u32;
+pub type SolutionIdBackingType = u64;
 
+// Solution ids are derived from the solution's edges, so they are stable between runs.
+// They are serialized as hex strings, as JavaScript numbers cannot represent all u64 values.
 #[derive(Copy, Clone, PartialOrd, Ord, Eq, PartialEq, Hash, Serialize, Deserialize)]
-#[serde(from = "SolutionIdBackingType", into = "SolutionIdBackingType")]
+#[serde(try_from = "String", into = "String")]
 pub struct Sol {
     pub id: SolutionIdBackingType,
 }
 
-impl From<Sol> for SolutionIdBackingType {
+impl From<Sol> for String {
     fn from(sol: Sol) -> Self {
-        sol.id
+        format!("{:016x}", sol.id)
     }
 }
 
-impl From<SolutionIdBackingType> for Sol {
-    fn from(id: SolutionIdBackingType) -> Self {
-        Self { id }
+impl TryFrom<String> for Sol {
+    type Error = std::num::ParseIntError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        SolutionIdBackingType::from_str_radix(&id, 16).map(|id| Self { id })
     }
 }
 
@@ -58,10 +62,19 @@ impl Sol {
         if let Some(sol) = ctx.id_to_solution.get_back(&solution) {
             *sol
         } else {
-            ctx.solution_id += 1;
-            let sol = Sol {
-                id: ctx.solution_id,
+            // Names are looked up directly, as the context is already locked.
+            let mut sol = Sol {
+                id: solution.content_hash(|ent| {
+                    ctx.id_to_type
+                        .get(ent)
+                        .expect("All entities should have a type")
+                        .to_string()
+                }),
             };
+            // Hash collisions are very unlikely, but would otherwise merge different solutions.
+            while ctx.id_to_solution.get(&sol).is_some() {
+                sol.id = sol.id.wrapping_add(1);
+            }
             Sol::new_with_id(ctx, sol, solution)
         }
     }
@@ -119,6 +132,10 @@ impl Sol {
         self.make_child(&|sol| sol.add_edge(from, to))
     }
 
+    pub fn remove_edge(&self, from: Ent, to: Ent) -> Sol {
+        self.make_child(&|sol| sol.remove_edge(from, to))
+    }
+
     pub fn has_edge(&self, from: Ent, to: Ent) -> bool {
         self.solution().has_edge(from, to)
     }
@@ -128,7 +145,7 @@ impl Sol {
         let ancestors: Vec<String> = self
             .ancestors()
             .iter()
-            .map(|anc| anc.id.to_string())
+            .map(|anc| String::from(*anc))
             .collect();
         ancestors.join(", ")
     }
@@ -164,7 +181,7 @@ impl std::fmt::Debug for Sol {
             .collect();
         let edges = edges.join(", ");
         f.debug_struct("Sol")
-            .field("id", &self.id)
+            .field("id", &Raw(String::from(*self)))
             .field("{ancestors}", &Raw(self.ancestor_string()))
             .field("{edges}", &Raw(edges))
             .finish()
@@ -6,11 +6,12 @@
 
 use crate::dot::{DotGraph, ToDot};
 use crate::recipes::{
-    Check, Claim, HasTag, Ibis, Leak, Node, Recipe, TrustedToRemoveTag, TrustedToRemoveTagFromNode,
-    TypeError,
+    CategoryLeak, Check, Claim, HasTag, Ibis, ImplicitFlow, InternalFlow, Leak, Node, Recipe,
+    TrustedToRemoveTag, TrustedToRemoveTagFromNode, TypeError,
 };
-use crate::Sol;
-use std::collections::{HashMap, HashSet};
+use crate::util::sort_by_name;
+use crate::{Ent, Sol};
+use std::collections::{BTreeMap, BTreeSet};
 
 impl ToDot for Ibis {
     fn to_dot_repr(&self) -> DotGraph {
@@ -70,13 +71,13 @@ impl ToDot for (&Ibis, &Recipe) {
         let (ibis, recipe) = &self;
         let sol = &recipe.id.unwrap_or_else(Sol::empty);
         let s_id = sol_id(sol);
-        let particle_id = |particle| format!("{}_p_{}", &s_id, particle);
+        let particle_id = |particle: &str| format!("{}_p_{}", &s_id, particle);
         let node_id = |node| format!("{}_h_{}", &s_id, node).replace('.', "_");
         let mut sol_graph = DotGraph::default();
-        let mut particles = HashMap::new();
+        let mut particles = BTreeMap::new();
         for Node(particle, node, ty) in &ibis.shared.nodes {
-            let mut extras: HashSet<String> = HashSet::new();
-            let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+            let mut extras: BTreeSet<String> = BTreeSet::new();
+            let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
             for HasTag(_hts, source, sink, tag) in &recipe.feedback.has_tags {
                 if sink == node && source != node {
                     tags.entry(tag.to_string())
@@ -129,23 +130,46 @@ impl ToDot for (&Ibis, &Recipe) {
                 .iter()
                 .map(|ex| format!("<tr><td>{}</td></tr>", ex))
                 .collect();
-            let particle_g = particles.entry(particle).or_insert_with(DotGraph::default);
+            let particle_g = particles
+                .entry(particle.to_string())
+                .or_insert_with(DotGraph::default);
             particle_g.add_node(format!("{node_id} [shape=record label=< <table border=\"0\"><tr><td>{node} : {ty}</td></tr>{extras}</table>>]", node_id=node_id(node), node=node, ty=ty, extras=extras.join("")));
         }
+        for InternalFlow(particle, from, to) in &ibis.shared.internal_flows {
+            if let Some(particle_g) = particles.get_mut(&particle.to_string()) {
+                particle_g.add_edge(
+                    node_id(from),
+                    node_id(to),
+                    vec!["style=dashed color=gray".to_string()],
+                );
+            }
+        }
         for (particle, particle_g) in particles {
-            sol_graph.add_child(particle_id(particle), format!("{}", particle), particle_g);
+            sol_graph.add_child(particle_id(&particle), particle, particle_g);
         }
 
         for Leak(_leak_s, node, expected, source, tag) in &recipe.feedback.leaks {
             sol_graph.add_edge(node_id(source), node_id(node), vec![format!("style=dotted color=red label=<<font color=\"red\">expected '{}', found '{}'</font>>", expected, tag)]);
         }
 
+        for ImplicitFlow(_flow_s, node, expected, source, tag) in &recipe.feedback.implicit_flows {
+            sol_graph.add_edge(node_id(source), node_id(node), vec![format!("style=dotted color=orange label=<<font color=\"orange\">implicit flow: expected '{}', found '{}'</font>>", expected, tag)]);
+        }
+
+        for CategoryLeak(_leak_s, category, node, expected, source, tag) in
+            &recipe.feedback.category_leaks
+        {
+            sol_graph.add_edge(node_id(source), node_id(node), vec![format!("style=dotted color=red label=<<font color=\"red\">{}: expected '{}', found '{}'</font>>", category, expected, tag)]);
+        }
+
         for TypeError(_error_s, from, from_ty, to, to_ty) in &recipe.feedback.type_errors {
             sol_graph.add_edge(node_id(from), node_id(to), vec![format!("style=dotted color=red label=<<font color=\"red\">expected '{}', found '{}'</font>>", to_ty, from_ty)]);
         }
 
         let sol = &recipe.id.unwrap_or_else(Sol::empty).solution();
-        for (from_id, to_id) in &sol.edges {
+        let mut edges: Vec<(Ent, Ent)> = sol.edges.iter().cloned().collect();
+        sort_by_name(&mut edges);
+        for (from_id, to_id) in &edges {
             let from = node_id(from_id).to_string();
             let to = node_id(to_id).to_string();
             sol_graph.add_edge(from, to, vec![]);
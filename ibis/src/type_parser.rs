@@ -4,13 +4,15 @@
 // license that can be found in the LICENSE file or at
 // https://developers.google.com/open-source/licenses/bsd
 extern crate nom;
+use crate::refinement::{Comparison, Constraint, Refinement, Subject};
 use crate::type_struct::*;
 use nom::{
-    bytes::complete::{tag, take_while1},
-    character::complete::{space0, space1},
-    combinator::{cut, opt},
-    multi::{separated_list0, separated_list1},
-    sequence::tuple,
+    branch::alt,
+    bytes::complete::{escaped, tag, take_while1},
+    character::complete::{anychar, digit1, none_of, satisfy, space0, space1},
+    combinator::{all_consuming, cut, map, map_res, not, opt, peek, recognize, value},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, preceded, terminated, tuple},
     Finish, IResult,
 };
 use std::sync::Arc;
@@ -18,7 +20,7 @@ use std::sync::Arc;
 fn is_name_char(c: char) -> bool {
     !matches!(
         c,
-        '(' | ')' | '{' | '}' | ',' | ':' | ' ' | '\n' | '\r' | '\t'
+        '(' | ')' | '{' | '}' | ',' | ':' | '?' | ' ' | '\n' | '\r' | '\t'
     )
 }
 fn is_lower_char(c: char) -> bool {
@@ -29,10 +31,123 @@ fn name<'a>() -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
     move |input: &'a str| take_while1(is_name_char)(input)
 }
 
-fn label<'a>() -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+fn capability_name<'a>() -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
     move |input: &'a str| {
-        let (input, (name, _)) = tuple((name(), tag(":")))(input)?;
-        Ok((input, name))
+        recognize(separated_list1(
+            alt((tag("."), tag(":"))),
+            take_while1(is_lower_char),
+        ))(input)
+    }
+}
+
+fn literal<'a>() -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input: &'a str| {
+        alt((
+            recognize(tuple((
+                tag("\""),
+                opt(escaped(none_of("\\\""), '\\', anychar)),
+                tag("\""),
+            ))),
+            terminated(
+                alt((
+                    recognize(tuple((opt(tag("-")), digit1))),
+                    tag("true"),
+                    tag("false"),
+                )),
+                not(peek(satisfy(is_name_char))),
+            ),
+        ))(input)
+    }
+}
+
+fn integer(input: &str) -> IResult<&str, i64> {
+    map_res(recognize(tuple((opt(tag("-")), digit1))), str::parse)(input)
+}
+
+fn comparison(input: &str) -> IResult<&str, Comparison> {
+    delimited(
+        space0,
+        alt((
+            value(Comparison::LessOrEqual, tag("<=")),
+            value(Comparison::GreaterOrEqual, tag(">=")),
+            value(Comparison::Equal, tag("==")),
+            value(Comparison::Less, tag("<")),
+            value(Comparison::Greater, tag(">")),
+        )),
+        space0,
+    )(input)
+}
+
+fn subject(input: &str) -> IResult<&str, Subject> {
+    terminated(
+        alt((
+            value(Subject::Value, tag("x")),
+            value(Subject::Length, tag("len")),
+        )),
+        not(peek(satisfy(|c: char| c.is_alphanumeric() || c == '_'))),
+    )(input)
+}
+
+fn pattern(input: &str) -> IResult<&str, Constraint> {
+    let (input, (_, _, _, _, pattern)) = tuple((
+        tag("x"),
+        space1,
+        tag("matches"),
+        space1,
+        delimited(
+            tag("/"),
+            opt(escaped(none_of("\\/"), '\\', anychar)),
+            tag("/"),
+        ),
+    ))(input)?;
+    Ok((
+        input,
+        Constraint::Matches(pattern.unwrap_or_default().to_string()),
+    ))
+}
+
+fn constraints(input: &str) -> IResult<&str, Vec<Constraint>> {
+    alt((
+        map(
+            tuple((integer, comparison, subject, comparison, integer)),
+            |(low, low_cmp, subject, high_cmp, high)| {
+                vec![
+                    Constraint::Compare(subject, low_cmp.flip(), low),
+                    Constraint::Compare(subject, high_cmp, high),
+                ]
+            },
+        ),
+        map(
+            tuple((integer, comparison, subject)),
+            |(low, cmp, subject)| vec![Constraint::Compare(subject, cmp.flip(), low)],
+        ),
+        map(pattern, |pattern| vec![pattern]),
+        map(
+            tuple((subject, comparison, integer)),
+            |(subject, cmp, high)| vec![Constraint::Compare(subject, cmp, high)],
+        ),
+    ))(input)
+}
+
+fn refinement(input: &str) -> IResult<&str, Refinement> {
+    let (input, constraints) = preceded(
+        tuple((tag("where"), space1)),
+        cut(separated_list1(
+            tuple((space1, tag("and"), space1)),
+            constraints,
+        )),
+    )(input)?;
+    let mut refinement = Refinement::default();
+    for constraint in constraints.into_iter().flatten() {
+        refinement.add(constraint);
+    }
+    Ok((input, refinement))
+}
+
+fn label<'a>() -> impl Fn(&'a str) -> IResult<&'a str, (&'a str, bool)> {
+    move |input: &'a str| {
+        let (input, (name, optional, _)) = tuple((name(), opt(tag("?")), tag(":")))(input)?;
+        Ok((input, (name, optional.is_some())))
     }
 }
 
@@ -48,11 +163,37 @@ pub trait TypeParser {
     }
 
     fn read_type(&mut self, input: &str) -> Arc<Type> {
-        self.store_type(input, &|s: &mut Self| s.read_type_uncached(input))
+        self.store_type(input, |s: &mut Self| s.read_type_uncached(input))
+    }
+
+    fn capability_term<'a>(&mut self, og_input: &'a str) -> IResult<&'a str, Arc<Type>> {
+        let (input, (name, args)) = tuple((
+            capability_name(),
+            opt(delimited(
+                tag("("),
+                separated_list1(tuple((space0, tag(","), space0)), |i| {
+                    self.capability_term(i)
+                }),
+                tag(")"),
+            )),
+        ))(og_input)?;
+        // Not cached, as 'id:int' is only a capability where one is expected (elsewhere it is a
+        // label).
+        Ok((
+            input,
+            Arc::new(Type::new(name).with_args(args.unwrap_or_default())),
+        ))
+    }
+
+    // Reads a capability on its own (e.g. 'fs:write' in the declared capabilities), falling back
+    // to reading it as a type (e.g. 'write(*)').
+    fn read_capability(&mut self, input: &str) -> Arc<Type> {
+        let cap = all_consuming(|i| self.capability_term(i))(input).map(|(_, cap)| cap);
+        cap.unwrap_or_else(|_| self.read_type(input))
     }
 
-    fn capability<'a>(&mut self, input: &'a str) -> IResult<&'a str, &'a str> {
-        let (input, (cap, _)) = tuple((take_while1(is_lower_char), space1))(input)?;
+    fn capability<'a>(&mut self, input: &'a str) -> IResult<&'a str, Arc<Type>> {
+        let (input, (cap, _)) = tuple((|i| self.capability_term(i), space1))(input)?;
         Ok((input, cap))
     }
 
@@ -83,16 +224,27 @@ pub trait TypeParser {
         ))
     }
 
+    fn literal_type<'a>(&mut self, input: &'a str) -> IResult<&'a str, Arc<Type>> {
+        let (input, literal) = literal()(input)?;
+        Ok((input, self.type_from_name(literal)))
+    }
+
     fn labelled_type<'a>(&mut self, og_input: &'a str) -> IResult<&'a str, Arc<Type>> {
-        let (input, (label, ty)) = tuple((label(), cut(|i| self.type_parser(i))))(og_input)?;
+        let (input, ((label, optional), ty)) =
+            tuple((label(), cut(|i| self.type_parser(i))))(og_input)?;
         let label = self.type_from_name(label);
+        let labelled = if optional {
+            OPTIONAL_LABELLED
+        } else {
+            LABELLED
+        };
         let covered = &og_input[0..og_input.len() - input.len()];
         Ok((
             input,
             self.store_type(covered, |s| {
                 // TODO: with_arg(s) shouldn't mutate.
                 Arc::new(
-                    (*s.type_from_name(LABELLED))
+                    (*s.type_from_name(labelled))
                         .clone()
                         .with_arg(label)
                         .with_arg(ty),
@@ -131,21 +283,56 @@ pub trait TypeParser {
         Ok((
             input,
             self.store_type(covered, |_self| {
-                Arc::new((*ty).clone().with_capability(cap))
+                Arc::new((*ty).clone().with_capability_term(cap))
             }),
         ))
     }
 
+    fn union_type<'a>(&mut self, og_input: &'a str) -> IResult<&'a str, Arc<Type>> {
+        let (input, first) = self.non_union_type(og_input)?;
+        let (input, rest) = many0(preceded(tag("|"), cut(|i| self.non_union_type(i))))(input)?;
+        if rest.is_empty() {
+            Ok((input, first))
+        } else {
+            let covered = &og_input[0..og_input.len() - input.len()];
+            Ok((
+                input,
+                self.store_type(covered, |s| {
+                    Arc::new(
+                        (*s.type_from_name(UNION))
+                            .clone()
+                            .with_arg(first)
+                            .with_args(rest),
+                    )
+                }),
+            ))
+        }
+    }
+
     fn type_parser<'a>(&mut self, input: &'a str) -> IResult<&'a str, Arc<Type>> {
+        self.union_type(input)
+    }
+
+    fn non_union_type<'a>(&mut self, input: &'a str) -> IResult<&'a str, Arc<Type>> {
         let (input, _) = space0(input)?;
         let (input, res) = self
             .parenthesized(input)
             .or_else(|_| self.product_type(input))
-            .or_else(|_| self.labelled_type(input))
+            .or_else(|_| self.literal_type(input))
             .or_else(|_| self.structure_with_capability(input))
+            .or_else(|_| self.labelled_type(input))
             .or_else(|_| self.simple_structure(input))?;
         let (input, _) = space0(input)?; // drop any following whitespace.
-        Ok((input, res))
+        self.refined_type(input, res)
+    }
+
+    fn refined_type<'a>(&mut self, input: &'a str, ty: Arc<Type>) -> IResult<&'a str, Arc<Type>> {
+        if let (input, Some(refinement)) = opt(refinement)(input)? {
+            let (input, _) = space0(input)?;
+            Ok((input, Arc::new((*ty).clone().with_refinement(refinement))))
+        } else {
+            Ok((input, ty))
+        }
     }
 
     fn read_type_uncached(&mut self, og_input: &str) -> Arc<Type> {
@@ -272,5 +459,208 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_type_with_label_without_whitespace() {
+        let ty = read_type("{name:String}");
+        assert_eq!(
+            ty,
+            Type::new(LABELLED)
+                .with_arg(Type::new("name"))
+                .with_arg(Type::new("String"))
+        );
+        assert_eq!(format!("{}", ty), "name: String");
+        // Lower case types are not mistaken for namespaced capabilities (e.g. fs:write).
+        assert_eq!(format!("{}", read_type("{id:int}")), "id: int");
+        assert_eq!(format!("{}", read_type("List(a:int)")), "List(a: int)");
+        assert_eq!(
+            format!("{}", read_type("x:ibis.UnionType(A, B)")),
+            "x: A | B"
+        );
+    }
+
+    #[test]
+    fn read_type_with_optional_label() {
+        parse_and_round_trip(
+            "age?: Int",
+            Type::new(OPTIONAL_LABELLED)
+                .with_arg(Type::new("age"))
+                .with_arg(Type::new("Int")),
+        );
+    }
+
+    #[test]
+    fn read_a_product_type_with_an_optional_field() {
+        let name_string = read_type("{name: String}");
+        let age_int = read_type("{age?: Int}");
+        parse_and_round_trip(
+            "{name: String, age?: Int}",
+            Type::new(PRODUCT).with_arg(name_string).with_arg(age_int),
+        );
+    }
+
+    #[test]
+    fn read_a_string_literal() {
+        parse_and_round_trip("\"small, or (tiny)\"", Type::new("\"small, or (tiny)\""));
+    }
+
+    #[test]
+    fn read_an_integer_literal() {
+        parse_and_round_trip("-42", Type::new("-42"));
+    }
+
+    #[test]
+    fn read_a_boolean_literal() {
+        parse_and_round_trip("true", Type::new("true"));
+    }
+
+    #[test]
+    fn read_a_name_starting_with_digits() {
+        parse_and_round_trip("3D", Type::new("3D"));
+    }
+
+    #[test]
+    fn read_a_union_of_literals() {
+        parse_and_round_trip(
+            "\"small\" | \"medium\" | \"large\"",
+            Type::new(UNION)
+                .with_arg(Type::new("\"small\""))
+                .with_arg(Type::new("\"medium\""))
+                .with_arg(Type::new("\"large\"")),
+        );
+    }
+
+    #[test]
+    fn read_a_labelled_union_in_a_product() {
+        parse_and_round_trip(
+            "{size: \"small\" | 1, name: String}",
+            Type::new(PRODUCT)
+                .with_arg(
+                    Type::new(LABELLED).with_arg(Type::new("size")).with_arg(
+                        Type::new(UNION)
+                            .with_arg(Type::new("\"small\""))
+                            .with_arg(Type::new("1")),
+                    ),
+                )
+                .with_arg(
+                    Type::new(LABELLED)
+                        .with_arg(Type::new("name"))
+                        .with_arg(Type::new("String")),
+                ),
+        );
+    }
+
+    #[test]
+    fn read_a_nested_union() {
+        parse_and_round_trip(
+            "(A | B) | C",
+            Type::new(UNION)
+                .with_arg(
+                    Type::new(UNION)
+                        .with_arg(Type::new("A"))
+                        .with_arg(Type::new("B")),
+                )
+                .with_arg(Type::new("C")),
+        );
+    }
+
+    #[test]
+    fn read_a_refined_type() {
+        let mut refinement = Refinement::default();
+        refinement.constrain(Subject::Value, Comparison::GreaterOrEqual, 0);
+        refinement.constrain(Subject::Value, Comparison::LessOrEqual, 99);
+        parse_and_round_trip(
+            "Int where 0 <= x <= 99",
+            Type::new("Int").with_refinement(refinement),
+        );
+    }
+
+    #[test]
+    fn read_a_refined_type_in_normal_form() {
+        assert_eq!(
+            read_type("Int where 0 <= x < 100"),
+            read_type("Int where x >= 0 and x <= 99")
+        );
+        assert_eq!(
+            format!("{}", read_type("String where len < 257")),
+            "String where len <= 256"
+        );
+    }
+
+    #[test]
+    fn read_a_refined_type_with_a_pattern() {
+        let mut refinement = Refinement::default();
+        refinement.constrain(Subject::Length, Comparison::LessOrEqual, 256);
+        refinement.add_pattern("[a-z\\/]+");
+        parse_and_round_trip(
+            "String where len <= 256 and x matches /[a-z\\/]+/",
+            Type::new("String").with_refinement(refinement),
+        );
+    }
+
+    #[test]
+    fn read_a_refined_field() {
+        let mut refinement = Refinement::default();
+        refinement.constrain(Subject::Value, Comparison::Equal, 1);
+        parse_and_round_trip(
+            "{count: Int where x == 1, name: String}",
+            Type::new(PRODUCT)
+                .with_arg(
+                    Type::new(LABELLED)
+                        .with_arg(Type::new("count"))
+                        .with_arg(Type::new("Int").with_refinement(refinement)),
+                )
+                .with_arg(
+                    Type::new(LABELLED)
+                        .with_arg(Type::new("name"))
+                        .with_arg(Type::new("String")),
+                ),
+        );
+    }
+
+    #[test]
+    fn read_a_type_with_a_namespaced_capability() {
+        parse_and_round_trip(
+            "net.read Type",
+            Type::new("Type").with_capability("net.read"),
+        );
+    }
+
+    #[test]
+    fn read_a_type_with_a_colon_namespaced_capability() {
+        parse_and_round_trip(
+            "fs:write Type",
+            Type::new("Type").with_capability("fs:write"),
+        );
+    }
+
+    #[test]
+    fn read_a_colon_namespaced_capability_on_its_own() {
+        let mut tp = TP {};
+        assert_eq!(*tp.read_capability("fs:write"), Type::new("fs:write"));
+        assert_eq!(
+            *tp.read_capability("write(*)"),
+            Type::new("write").with_arg(Type::new("*"))
+        );
+    }
+
+    #[test]
+    fn read_a_type_with_a_capability_with_arguments() {
+        parse_and_round_trip(
+            "write(append, fs:local) Type",
+            Type::new("Type").with_capability_term(
+                Type::new("write")
+                    .with_arg(Type::new("append"))
+                    .with_arg(Type::new("fs:local")),
+            ),
+        );
+    }
+
+    #[test]
+    fn capabilities_are_read_the_same_way_as_types() {
+        let cap = read_type("write(append)");
+        let ty = read_type("write(append) Type");
+        assert_eq!(*ty.args[0], cap);
+    }
+
     // TODO: tests for error messages
 }
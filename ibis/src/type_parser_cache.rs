@@ -44,6 +44,12 @@ pub fn read_type(input: &str) -> Arc<Type> {
     ctx.borrow_mut().read_type(input)
 }
 
+pub fn read_capability(input: &str) -> Arc<Type> {
+    let guard = PARSE_CACHE.lock().expect("Shouldn't fail");
+    let mut ctx = (*guard).borrow_mut();
+    ctx.borrow_mut().read_capability(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
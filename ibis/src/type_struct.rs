@@ -3,6 +3,7 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file or at
 // https://developers.google.com/open-source/licenses/bsd
+use crate::refinement::Refinement;
 use std::sync::Arc;
 
 pub const UNIVERSAL: &str = "ibis.UniversalType";
@@ -12,11 +13,16 @@ pub const UNION: &str = "ibis.UnionType";
 pub const GENERIC: &str = "ibis.GenericType";
 pub const INDUCTIVE: &str = "ibis.InductiveType";
 pub const LABELLED: &str = "ibis.Labelled";
+pub const OPTIONAL_LABELLED: &str = "ibis.OptionalLabelled";
+pub const STRING: &str = "String";
+pub const INT: &str = "Int";
+pub const BOOLEAN: &str = "Boolean";
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Type {
     pub name: String,
     pub args: Vec<Arc<Type>>,
+    pub refinement: Option<Refinement>,
 }
 
 impl Type {
@@ -27,6 +33,7 @@ impl Type {
         Self {
             name: name.to_string(),
             args: vec![],
+            refinement: None,
         }
     }
     pub fn with_args<T: Into<Arc<Type>>>(mut self, mut args: Vec<T>) -> Self {
@@ -38,7 +45,101 @@ impl Type {
         self
     }
     pub fn with_capability(self, cap: &str) -> Self {
-        Self::new(WITH_CAPABILITY).with_args(vec![Type::new(cap), self])
+        self.with_capability_term(Type::new(cap))
+    }
+    pub fn with_capability_term<T: Into<Arc<Type>>>(self, cap: T) -> Self {
+        Self::new(WITH_CAPABILITY).with_args(vec![cap.into(), Arc::new(self)])
+    }
+    pub fn with_refinement(mut self, refinement: Refinement) -> Self {
+        self.refinement = Some(refinement);
+        self
+    }
+    // The type without its refinement, if it has one.
+    pub fn unrefined(&self) -> Option<Self> {
+        self.refinement.as_ref().map(|_| Self {
+            refinement: None,
+            ..self.clone()
+        })
+    }
+    // The base type of a literal type (e.g. "small", 42 or true), if this is one.
+    pub fn literal_base(&self) -> Option<&'static str> {
+        if !self.args.is_empty() || self.refinement.is_some() {
+            None
+        } else if self.name.len() > 1 && self.name.starts_with('"') && self.name.ends_with('"') {
+            Some(STRING)
+        } else if self.name.parse::<i64>().is_ok() {
+            Some(INT)
+        } else if self.name == "true" || self.name == "false" {
+            Some(BOOLEAN)
+        } else {
+            None
+        }
+    }
+    fn needs_parens_in_union(&self) -> bool {
+        !self.args.is_empty()
+            && (self.name == UNION
+                || self.name == WITH_CAPABILITY
+                || self.name == LABELLED
+                || self.name == OPTIONAL_LABELLED)
+    }
+}
+
+// The order that fields of a product are kept in (by label, then by type).
+fn field_order(field: &Arc<Type>) -> (String, String) {
+    let label = if field.name == LABELLED || field.name == OPTIONAL_LABELLED {
+        field.args.first().map(|label| label.to_string())
+    } else {
+        None
+    };
+    (label.unwrap_or_default(), field.to_string())
+}
+
+// The canonical form of a type, so that structurally equal types are interned as the same entity.
+// Product fields and union members are sorted, nested products and unions are flattened, duplicate
+// members are removed and products or unions of a single member are replaced by that member.
+pub fn normalize(ty: Arc<Type>) -> Arc<Type> {
+    if ty.args.is_empty() {
+        return ty;
+    }
+    let args: Vec<Arc<Type>> = ty.args.iter().cloned().map(normalize).collect();
+    let args = if (ty.name == PRODUCT || ty.name == UNION) && ty.refinement.is_none() {
+        let mut members: Vec<Arc<Type>> = vec![];
+        for arg in args {
+            let nested = if arg.name == ty.name && arg.refinement.is_none() {
+                arg.args.clone()
+            } else {
+                vec![arg]
+            };
+            for member in nested {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        if ty.name == PRODUCT {
+            members.sort_by_cached_key(field_order);
+        } else {
+            members.sort_by_cached_key(|member| member.to_string());
+        }
+        if members.len() == 1 {
+            return members.pop().expect("Members should have a single element");
+        }
+        members
+    } else {
+        args
+    };
+    let unchanged = args.len() == ty.args.len()
+        && args
+            .iter()
+            .zip(ty.args.iter())
+            .all(|(arg, original)| Arc::ptr_eq(arg, original));
+    if unchanged {
+        ty
+    } else {
+        Arc::new(Type {
+            args,
+            ..(*ty).clone()
+        })
     }
 }
 
@@ -58,7 +159,13 @@ fn format_arg_set(
 
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.name == WITH_CAPABILITY && self.args.len() > 1 {
+        if let (Some(refinement), Some(base)) = (&self.refinement, self.unrefined()) {
+            if base.needs_parens_in_union() {
+                write!(f, "({}) where {}", base, refinement)
+            } else {
+                write!(f, "{} where {}", base, refinement)
+            }
+        } else if self.name == WITH_CAPABILITY && self.args.len() > 1 {
             write!(f, "{} ", self.args[0])?;
             if self.args.len() > 2 {
                 write!(f, "(")?;
@@ -78,6 +185,28 @@ impl std::fmt::Display for Type {
                 write!(f, ")")?;
             }
             Ok(())
+        } else if self.name == OPTIONAL_LABELLED && self.args.len() > 1 {
+            write!(f, "{}?: ", self.args[0])?;
+            if self.args.len() > 2 {
+                write!(f, "(")?;
+            }
+            format_arg_set(f, ", ", &self.args[1..])?;
+            if self.args.len() > 2 {
+                write!(f, ")")?;
+            }
+            Ok(())
+        } else if self.name == UNION && self.args.len() > 1 {
+            for (i, arg) in self.args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " | ")?;
+                }
+                if arg.needs_parens_in_union() {
+                    write!(f, "({})", arg)?;
+                } else {
+                    write!(f, "{}", arg)?;
+                }
+            }
+            Ok(())
         } else if self.name == PRODUCT && !self.args.is_empty() {
             write!(f, "{{")?;
             format_arg_set(f, ", ", &self.args)?;
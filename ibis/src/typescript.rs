@@ -0,0 +1,363 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+extern crate nom;
+use crate::recipes::{Config, SubtypeInput};
+use crate::type_struct::*;
+use crate::Ent;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_until, take_while},
+    character::complete::{digit1, multispace1, satisfy},
+    combinator::{map, not, opt, peek, recognize, value},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+// The result of importing TypeScript declarations.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TypeScriptImport {
+    // Subtyping relations implied by the declarations, other than their aliases.
+    pub subtypes: Vec<SubtypeInput>,
+    // Each declared name, and the type it is defined as (a subtype in both directions).
+    pub aliases: Vec<(Ent, Ent)>,
+    // Declarations that could not be imported.
+    pub warnings: Vec<String>,
+}
+
+impl TypeScriptImport {
+    pub fn to_config(&self) -> Config {
+        let aliases = self
+            .aliases
+            .iter()
+            .flat_map(|(name, body)| [SubtypeInput(*name, *body), SubtypeInput(*body, *name)]);
+        Config {
+            subtypes: aliases.chain(self.subtypes.iter().cloned()).collect(),
+            ..Config::default()
+        }
+    }
+}
+
+// A declared type parameter, e.g. T in Array<T>.
+type Params = BTreeSet<String>;
+
+enum Declaration {
+    Interface {
+        name: String,
+        params: Vec<String>,
+        extends: Vec<Type>,
+        body: Type,
+    },
+    Alias {
+        name: String,
+        params: Vec<String>,
+        body: Type,
+    },
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+// Whitespace and comments.
+fn ws(input: &str) -> IResult<&str, ()> {
+    value(
+        (),
+        many0(alt((
+            multispace1,
+            recognize(pair(tag("//"), opt(is_not("\n")))),
+            recognize(tuple((tag("/*"), take_until("*/"), tag("*/")))),
+        ))),
+    )(input)
+}
+
+fn token<'a>(t: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    preceded(ws, tag(t))
+}
+
+fn keyword<'a>(t: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    preceded(ws, terminated(tag(t), not(peek(satisfy(is_ident_char)))))
+}
+
+fn ident(input: &str) -> IResult<&str, &str> {
+    preceded(
+        ws,
+        recognize(pair(satisfy(is_ident_start), take_while(is_ident_char))),
+    )(input)
+}
+
+fn string_literal(input: &str) -> IResult<&str, String> {
+    preceded(
+        ws,
+        alt((
+            delimited(tag("\""), take_while(|c| c != '"'), tag("\"")),
+            delimited(tag("'"), take_while(|c| c != '\''), tag("'")),
+        )),
+    )(input)
+    .map(|(input, s)| (input, format!("\"{}\"", s)))
+}
+
+fn number_literal(input: &str) -> IResult<&str, &str> {
+    preceded(ws, recognize(pair(opt(tag("-")), digit1)))(input)
+}
+
+fn type_params(input: &str) -> IResult<&str, Vec<String>> {
+    let param = terminated(
+        ident,
+        tuple((
+            opt(preceded(keyword("extends"), |i| ts_type(i, &Params::new()))),
+            opt(preceded(token("="), |i| ts_type(i, &Params::new()))),
+        )),
+    );
+    map(
+        delimited(token("<"), separated_list1(token(","), param), token(">")),
+        |params| params.iter().map(|p| p.to_string()).collect(),
+    )(input)
+}
+
+fn ibis_name(name: &str) -> &str {
+    match name {
+        "Array" | "ReadonlyArray" => "List",
+        "ReadonlySet" => "Set",
+        "ReadonlyMap" => "Map",
+        _ => name,
+    }
+}
+
+fn reference<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    let (input, name) = ident(input)?;
+    if params.contains(name) {
+        return Ok((input, Type::new(UNIVERSAL))); // Type parameters can be any type.
+    }
+    let (input, args) = opt(delimited(
+        token("<"),
+        separated_list1(token(","), |i| ts_type(i, params)),
+        token(">"),
+    ))(input)?;
+    Ok((
+        input,
+        Type::new(ibis_name(name)).with_args(args.unwrap_or_default()),
+    ))
+}
+
+fn member<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    let (input, (_, label, optional, _, ty, _)) = tuple((
+        opt(keyword("readonly")),
+        alt((ident, preceded(ws, digit1))),
+        opt(token("?")),
+        token(":"),
+        |i| ts_type(i, params),
+        opt(alt((token(";"), token(",")))),
+    ))(input)?;
+    let labelled = if optional.is_some() {
+        OPTIONAL_LABELLED
+    } else {
+        LABELLED
+    };
+    Ok((
+        input,
+        Type::new(labelled).with_arg(Type::new(label)).with_arg(ty),
+    ))
+}
+
+fn object<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    let (input, mut members) =
+        delimited(token("{"), many0(|i| member(i, params)), token("}"))(input)?;
+    if members.len() == 1 {
+        Ok((
+            input,
+            members.pop().expect("Members should have a single element"),
+        ))
+    } else {
+        Ok((input, Type::new(PRODUCT).with_args(members)))
+    }
+}
+
+fn primary<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    alt((
+        delimited(token("("), |i| ts_type(i, params), token(")")),
+        |i| object(i, params),
+        map(string_literal, |s| Type::new(&s)),
+        map(number_literal, Type::new),
+        |i| reference(i, params),
+    ))(input)
+}
+
+fn postfix<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    let (input, (mut ty, arrays)) =
+        pair(|i| primary(i, params), many0(pair(token("["), token("]"))))(input)?;
+    for _ in arrays {
+        ty = Type::new("List").with_arg(ty);
+    }
+    Ok((input, ty))
+}
+
+fn ts_type<'a>(input: &'a str, params: &Params) -> IResult<&'a str, Type> {
+    let (input, mut members) = preceded(
+        opt(token("|")),
+        separated_list1(token("|"), |i| postfix(i, params)),
+    )(input)?;
+    if members.len() == 1 {
+        Ok((
+            input,
+            members.pop().expect("Members should have a single element"),
+        ))
+    } else {
+        Ok((input, Type::new(UNION).with_args(members)))
+    }
+}
+
+fn modifiers(input: &str) -> IResult<&str, ()> {
+    value((), many0(alt((keyword("export"), keyword("declare")))))(input)
+}
+
+fn interface(input: &str) -> IResult<&str, Declaration> {
+    let (input, (_, _, name, params)) =
+        tuple((modifiers, keyword("interface"), ident, opt(type_params)))(input)?;
+    let params = params.unwrap_or_default();
+    let scope: Params = params.iter().cloned().collect();
+    let (input, (extends, body)) = tuple((
+        opt(preceded(
+            keyword("extends"),
+            separated_list1(token(","), |i| reference(i, &scope)),
+        )),
+        |i| object(i, &scope),
+    ))(input)?;
+    Ok((
+        input,
+        Declaration::Interface {
+            name: name.to_string(),
+            params,
+            extends: extends.unwrap_or_default(),
+            body,
+        },
+    ))
+}
+
+fn alias(input: &str) -> IResult<&str, Declaration> {
+    let (input, (_, _, name, params)) =
+        tuple((modifiers, keyword("type"), ident, opt(type_params)))(input)?;
+    let params = params.unwrap_or_default();
+    let scope: Params = params.iter().cloned().collect();
+    let (input, (_, body, _)) =
+        tuple((token("="), |i| ts_type(i, &scope), opt(token(";"))))(input)?;
+    Ok((
+        input,
+        Declaration::Alias {
+            name: name.to_string(),
+            params,
+            body,
+        },
+    ))
+}
+
+// Skips an unsupported statement (up to a ';' or the end of a '{...}' block).
+fn skip_statement(input: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                // A block at the end of a line ends the statement (e.g. 'namespace X { ... }').
+                let rest = &input[i + 1..];
+                let line = rest.split('\n').next().unwrap_or_default().trim();
+                if depth <= 0 && (line.is_empty() || line == ";") {
+                    let end = i + 1 + rest.find('\n').unwrap_or(rest.len());
+                    return (&input[end..], input[..end].trim_end());
+                }
+            }
+            ';' if depth == 0 => return (&input[i + 1..], &input[..i + 1]),
+            _ => {}
+        }
+    }
+    ("", input)
+}
+
+fn declared_type(name: &str, params: &[String]) -> Type {
+    Type::new(name).with_args(params.iter().map(|_| Type::new(UNIVERSAL)).collect())
+}
+
+fn ent(ty: Type) -> Ent {
+    Ent::by_type(Arc::new(ty))
+}
+
+impl TypeScriptImport {
+    fn add_generic(&mut self, name: &str, params: &[String]) {
+        if !params.is_empty() {
+            // Both are needed to relate instances by their arguments, e.g. Box<number> <: Box<*>.
+            for kind in [GENERIC, INDUCTIVE] {
+                self.subtypes
+                    .push(SubtypeInput(ent(Type::new(name)), ent(Type::new(kind))));
+            }
+        }
+    }
+
+    fn add(&mut self, declaration: Declaration) {
+        match declaration {
+            Declaration::Interface {
+                name,
+                params,
+                extends,
+                body,
+            } => {
+                self.add_generic(&name, &params);
+                let declared = ent(declared_type(&name, &params));
+                if extends.is_empty() {
+                    if !body.args.is_empty() {
+                        self.aliases.push((declared, ent(body)));
+                    }
+                } else {
+                    // The body only lists the interface's own fields, so records with those
+                    // fields are not necessarily instances of the parents (or the interface).
+                    for parent in extends {
+                        self.subtypes.push(SubtypeInput(declared, ent(parent)));
+                    }
+                    if !body.args.is_empty() {
+                        self.subtypes.push(SubtypeInput(declared, ent(body)));
+                    }
+                }
+            }
+            Declaration::Alias { name, params, body } => {
+                self.add_generic(&name, &params);
+                self.aliases
+                    .push((ent(declared_type(&name, &params)), ent(body)));
+            }
+        }
+    }
+}
+
+// Imports the interfaces and type aliases from a TypeScript declaration (.d.ts) file.
+// Other statements are skipped, with a warning.
+pub fn import_typescript(source: &str) -> TypeScriptImport {
+    let mut result = TypeScriptImport::default();
+    let mut input = source;
+    loop {
+        input = ws(input).map_or(input, |(input, _)| input);
+        if input.is_empty() {
+            break;
+        }
+        if let Ok((rest, declaration)) = alt((interface, alias))(input) {
+            result.add(declaration);
+            input = rest;
+        } else {
+            let (rest, skipped) = skip_statement(input);
+            result.warnings.push(format!(
+                "Skipped unsupported TypeScript: '{}'",
+                skipped.split_whitespace().collect::<Vec<&str>>().join(" ")
+            ));
+            input = rest;
+        }
+    }
+    result
+}
@@ -55,10 +55,10 @@ impl<T: Ord + Eq + Hash + Clone, U: Ord + Eq + Hash + Clone> BiMap<T, U> {
         self.forward.get(t)
     }
 
-    pub fn get_back<Q: ?Sized>(&self, u: &Q) -> Option<&T>
+    pub fn get_back<Q>(&self, u: &Q) -> Option<&T>
     where
         U: std::borrow::Borrow<Q>,
-        Q: Ord + Hash + Eq,
+        Q: Ord + Hash + Eq + ?Sized,
     {
         self.back.get(u)
     }
@@ -7,6 +7,8 @@
 mod bimap;
 pub use bimap::*;
 
+use crate::Ent;
+
 #[macro_export]
 macro_rules! map {
     () => {
@@ -60,3 +62,46 @@ pub fn make<
 ) -> Res {
     items.into_iter().map(f).collect()
 }
+
+// Items that are sorted by the names of their entities, rather than by the order in which their
+// entities happened to be interned.
+pub trait Names {
+    fn add_names(&self, names: &mut Vec<String>);
+}
+
+impl Names for Ent {
+    fn add_names(&self, names: &mut Vec<String>) {
+        names.push(self.to_string());
+    }
+}
+
+impl<T: Names> Names for Option<T> {
+    fn add_names(&self, names: &mut Vec<String>) {
+        if let Some(item) = self {
+            item.add_names(names);
+        }
+    }
+}
+
+impl<T: Names> Names for Vec<T> {
+    fn add_names(&self, names: &mut Vec<String>) {
+        for item in self {
+            item.add_names(names);
+        }
+    }
+}
+
+impl<A: Names, B: Names> Names for (A, B) {
+    fn add_names(&self, names: &mut Vec<String>) {
+        self.0.add_names(names);
+        self.1.add_names(names);
+    }
+}
+
+pub fn sort_by_name<T: Names>(items: &mut [T]) {
+    items.sort_by_cached_key(|item| {
+        let mut names = vec![];
+        item.add_names(&mut names);
+        names
+    });
+}
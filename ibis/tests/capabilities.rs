@@ -0,0 +1,170 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use ibis::{get_solutions, EdgeCapability, Ibis};
+use pretty_assertions::assert_eq;
+use utils::all_edges;
+
+#[test]
+fn implied_capabilities_are_inherited() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["write", "read"],
+    ["append", "read"]
+  ],
+  "capability_implies": [
+    ["readwrite", "read"],
+    ["readwrite", "write"],
+    ["write", "append"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "editor", "readwrite Text"],
+        ["p_b", "viewer", "read Text"],
+        ["p_c", "logger", "append Text"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> =
+        vec!["editor -> viewer, logger -> editor, logger -> viewer".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn undeclared_capabilities_generate_warnings() {
+    let data = r#"
+{
+  "capabilities": [
+    ["write", "read"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "write Text"],
+        ["p_b", "b", "raed Text"],
+        ["p_c", "c", "read wirte Text"]
+      ]
+    }
+  ]
+}
+"#;
+    let results: Ibis = get_solutions(data, None);
+    assert_eq!(
+        results.shared.warnings,
+        vec![
+            "Node 'b' uses the undeclared capability 'raed'",
+            "Node 'c' uses the undeclared capability 'wirte'",
+        ]
+    );
+}
+
+#[test]
+fn edges_are_explained_by_capability_pairs() {
+    let data = r#"
+{
+  "capabilities": [
+    ["write", "read"],
+    ["write", "any"]
+  ],
+  "capability_implies": [
+    ["readwrite", "read"],
+    ["readwrite", "write"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "write Text"],
+        ["p_b", "b", "readwrite Text"]
+      ],
+      "edges": [
+        ["a", "b"]
+      ]
+    }
+  ]
+}
+"#;
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut pairs: Vec<String> = recipe
+        .feedback
+        .edge_capabilities
+        .iter()
+        .map(|EdgeCapability(_s, from, to, from_cap, to_cap)| {
+            format!("{} -> {}: {} -> {}", from, to, from_cap, to_cap)
+        })
+        .collect();
+    pairs.sort();
+    assert_eq!(pairs, vec!["a -> b: write -> read"]);
+}
+
+#[test]
+fn namespaced_capabilities_are_distinct() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["net.write", "net.read"],
+    ["fs:write", "fs:read"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "download", "net.write Bytes"],
+        ["p_b", "upload", "net.read Bytes"],
+        ["p_c", "save", "fs:write Bytes"],
+        ["p_d", "load", "fs:read Bytes"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["download -> upload, save -> load".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn parameterised_capabilities_match_patterns() {
+    let data = r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["write(*)", "read"],
+    ["write(overwrite)", "read(latest)"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "log", "write(append) Text"],
+        ["p_b", "doc", "write(overwrite) Text"],
+        ["p_c", "reader", "read Text"],
+        ["p_d", "latest", "read(latest) Text"]
+      ]
+    }
+  ]
+}"#;
+    let solutions = all_edges(data);
+    let expected: Vec<String> = vec!["doc -> latest, doc -> reader, log -> reader".to_string()];
+    assert_eq!(solutions, expected);
+    let results: Ibis = get_solutions(data, None);
+    assert_eq!(results.shared.warnings, Vec::<String>::new());
+}
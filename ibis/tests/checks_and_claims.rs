@@ -54,7 +54,7 @@ fn create_tagged_type_checked_graphs() {
   ]
 }"#,
     );
-    let expected: Vec<String> = vec![
+    let expected: Vec<String> = [
         "",
         "a -> b",
         "a -> b, b -> e",
@@ -0,0 +1,119 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Counterexample, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// A private contact list is copied into a public feed, among other unrelated flows.
+const DATA: &str = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_contacts", "contacts", "write Data"],
+        ["p_weather", "weather", "write Data"],
+        ["p_copy", "copy_in", "read Data"],
+        ["p_copy", "copy_out", "write Data"],
+        ["p_feed", "feed", "read Data"],
+        ["p_feed", "feed_status", "write Data"],
+        ["p_log", "log", "read Data"]
+      ],
+      "edges": [
+        ["weather", "copy_in"],
+        ["contacts", "copy_in"],
+        ["copy_out", "feed"],
+        ["weather", "feed"],
+        ["feed_status", "log"],
+        ["contacts", "log"]
+      ],
+      "claims": [["contacts", "private"]],
+      "checks": [["feed", "public"]]
+    }
+  ]
+}"#;
+
+fn describe(counterexample: &Counterexample) -> String {
+    let Leak(_, node, _, source, tag) = counterexample.leak;
+    let mut edges: Vec<String> = counterexample
+        .edges
+        .iter()
+        .map(|(from, to)| format!("{} -> {}", from, to))
+        .collect();
+    let mut particles: Vec<String> = counterexample
+        .particles
+        .iter()
+        .map(|particle| particle.to_string())
+        .collect();
+    edges.sort();
+    particles.sort();
+    format!(
+        "{} <- {} ({}) via {} in {}",
+        node,
+        source,
+        tag,
+        edges.join(", "),
+        particles.join(", ")
+    )
+}
+
+#[test]
+fn counterexamples_only_keep_the_edges_needed_for_the_leak() {
+    let results: Ibis = get_solutions(DATA, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    assert_eq!(recipe.feedback.leaks.len(), 1);
+    let counterexamples: Vec<String> = results
+        .counterexamples(recipe)
+        .iter()
+        .map(describe)
+        .collect();
+    assert_eq!(
+        counterexamples,
+        vec![
+            "feed <- contacts (private) via contacts -> copy_in, copy_out -> feed in p_contacts, p_copy, p_feed"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn recipes_without_leaks_have_no_counterexamples() {
+    let data = DATA.replace(r#"["contacts", "copy_in"],"#, "");
+    let results: Ibis = get_solutions(&data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    assert_eq!(results.counterexamples(recipe), vec![]);
+}
+
+#[test]
+fn integrity_violations_have_no_counterexamples() {
+    let data = DATA
+        .replace(
+            r#""less_private_than""#,
+            r#""less_trusted_than": [["untrusted", "trusted"]],
+  "less_private_than""#,
+        )
+        .replace(
+            r#""claims": [["contacts", "private"]],
+      "checks": [["feed", "public"]]"#,
+            r#""integrity_claims": [["contacts", "untrusted"]],
+      "integrity_checks": [["feed", "trusted"]]"#,
+        );
+    let results: Ibis = get_solutions(&data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    assert_eq!(recipe.feedback.integrity_violations.len(), 1);
+    assert_eq!(results.counterexamples(recipe), vec![]);
+}
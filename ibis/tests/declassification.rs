@@ -0,0 +1,110 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Declassification, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// A camera frame containing faces is blurred and then both displayed and uploaded.
+fn blur_recipe(declassification: &str) -> String {
+    format!(
+        r#"
+{{
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "face"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_camera", "frame", "write Image"],
+        ["p_blur", "blur_in", "read Image"],
+        ["p_blur", "blur_out", "write Image"],
+        ["p_display", "display", "read Image"],
+        ["p_cloud", "upload", "read Image"]
+      ],
+      "edges": [["frame", "blur_in"], ["blur_out", "display"], ["blur_out", "upload"]],
+      "claims": [["frame", "face"]],
+      "checks": [["display", "public"], ["upload", "public"]],
+      {}
+    }}
+  ]
+}}"#,
+        declassification
+    )
+}
+
+fn leaks_and_declassifications(data: &str) -> (Vec<String>, Vec<String>) {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut leaks: Vec<String> = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, _, source, tag)| format!("{} <- {} ({})", node, source, tag))
+        .collect();
+    leaks.sort();
+    let mut declassifications: Vec<String> = recipe
+        .feedback
+        .declassifications
+        .iter()
+        .map(|Declassification(_, from, to, tag)| format!("{} -> {} ({})", from, to, tag))
+        .collect();
+    declassifications.sort();
+    (leaks, declassifications)
+}
+
+#[test]
+fn declassification_can_be_limited_to_destination_nodes() {
+    let data = blur_recipe(r#""trusted_to_remove_tag_into": [["blur_out", "face", "display"]]"#);
+    assert_eq!(
+        leaks_and_declassifications(&data),
+        (
+            vec!["upload <- frame (face)".to_string()],
+            vec!["blur_out -> display (face)".to_string()]
+        )
+    );
+}
+
+#[test]
+fn declassification_can_be_limited_to_purposes() {
+    let data = blur_recipe(
+        r#""purposes": [["display", "rendering"]],
+      "trusted_to_remove_tag_for_purpose": [["blur_out", "face", "rendering"]]"#,
+    );
+    assert_eq!(
+        leaks_and_declassifications(&data),
+        (
+            vec!["upload <- frame (face)".to_string()],
+            vec!["blur_out -> display (face)".to_string()]
+        )
+    );
+}
+
+#[test]
+fn unconditional_declassification_is_audited() {
+    let data = blur_recipe(r#""trusted_to_remove_tag": [["blur_out", "face"]]"#);
+    assert_eq!(
+        leaks_and_declassifications(&data),
+        (vec![], vec!["blur_in -> blur_out (face)".to_string()])
+    );
+}
+
+#[test]
+fn unused_declassification_is_not_audited() {
+    let data =
+        blur_recipe(r#""trusted_to_remove_tag_into": [["blur_out", "location", "display"]]"#);
+    assert_eq!(
+        leaks_and_declassifications(&data),
+        (
+            vec![
+                "display <- frame (face)".to_string(),
+                "upload <- frame (face)".to_string()
+            ],
+            vec![]
+        )
+    );
+}
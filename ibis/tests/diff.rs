@@ -0,0 +1,121 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ent, Ibis, IbisDiff};
+use pretty_assertions::assert_eq;
+
+const CHECKING: &str = include_str!("../examples/checking.json");
+const DEMO: &str = include_str!("../examples/demo.json");
+
+fn diff(before: &str, after: &str) -> IbisDiff {
+    Ibis::diff(&get_solutions(before, None), &get_solutions(after, None))
+}
+
+fn edges(edges: &[(Ent, Ent)]) -> Vec<String> {
+    edges
+        .iter()
+        .map(|(from, to)| format!("{} -> {}", from, to))
+        .collect()
+}
+
+#[test]
+fn unchanged_recipes_have_no_differences() {
+    assert!(diff(CHECKING, CHECKING).is_empty());
+}
+
+#[test]
+fn changing_a_handle_type_fixes_type_errors() {
+    let after = CHECKING.replace("write NotInt", "write Int");
+    let changes = diff(CHECKING, &after);
+    assert_eq!(changes.changed_solutions.len(), 1);
+    let solution = &changes.changed_solutions[0];
+    assert_eq!(edges(&solution.edges), vec!["a -> b"]);
+    assert_eq!(edges(&solution.fixed_type_errors), vec!["a -> b"]);
+    assert_eq!(solution.new_type_errors, vec![]);
+}
+
+#[test]
+fn adding_a_claim_reports_new_leaks() {
+    let after = CHECKING.replace(
+        r#""edges": ["#,
+        r#""claims": [["a", "private"]],
+      "checks": [["b", "public"]],
+      "edges": ["#,
+    );
+    let changes = diff(CHECKING, &after);
+    assert_eq!(changes.changed_solutions.len(), 1);
+    let leaks: Vec<String> = changes.changed_solutions[0]
+        .new_leaks
+        .iter()
+        .map(|leak| {
+            format!(
+                "{} <- {} ({}), expected {}",
+                leak.node, leak.source, leak.tag, leak.expected
+            )
+        })
+        .collect();
+    assert_eq!(leaks, vec!["b <- a (private), expected public"]);
+    assert_eq!(changes.changed_solutions[0].fixed_leaks, vec![]);
+}
+
+#[test]
+fn adding_a_check_loses_planned_solutions() {
+    let after = DEMO.replace(r#"["e", "pubic"]"#, r#"["e", "pubic"], ["d", "public"]"#);
+    let changes = diff(DEMO, &after);
+    assert!(!changes.lost_solutions.is_empty());
+    assert_eq!(changes.new_solutions, Vec::<Vec<(Ent, Ent)>>::new());
+    for lost in &changes.lost_solutions {
+        assert!(
+            edges(lost).contains(&"a -> d".to_string()),
+            "Only solutions leaking from a to d should be lost, not {:?}",
+            edges(lost)
+        );
+    }
+}
+
+#[test]
+fn adding_an_integrity_check_reports_new_violations() {
+    let recipe = |checks: &str| {
+        format!(
+            r#"
+{{
+  "capabilities": [["write", "read"]],
+  "less_trusted_than": [["untrusted", "trusted"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "input", "write Query"],
+        ["p_db", "sql", "read Query"]
+      ],
+      "edges": [["input", "sql"]],
+      "integrity_claims": [["input", "untrusted"]],
+      "integrity_checks": [{}]
+    }}
+  ]
+}}"#,
+            checks
+        )
+    };
+    let changes = diff(&recipe(""), &recipe(r#"["sql", "trusted"]"#));
+    assert_eq!(changes.changed_solutions.len(), 1);
+    let solution = &changes.changed_solutions[0];
+    let violations: Vec<String> = solution
+        .new_integrity_violations
+        .iter()
+        .map(|violation| {
+            format!(
+                "{} <- {} ({}), expected {}",
+                violation.node, violation.source, violation.tag, violation.expected
+            )
+        })
+        .collect();
+    assert_eq!(
+        violations,
+        vec!["sql <- input (untrusted), expected trusted"]
+    );
+    assert_eq!(solution.new_leaks, vec![]);
+    assert_eq!(solution.fixed_integrity_violations, vec![]);
+}
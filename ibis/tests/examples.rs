@@ -0,0 +1,135 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+// Checks each example in examples/ against its expected output in examples/expected/.
+// The JavaScript recipes (*.mjs, e.g. in examples/quill_tests/) are not checked, as they need
+// converting to Ibis recipes by playground/converter.js first.
+// Run with BLESS=1 to (re)write the expected outputs after an intended change, e.g.
+//   BLESS=1 cargo test --test examples
+
+use ibis::{Ibis, Leak, Recipe, TypeError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+struct ExpectedSolution {
+    edges: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    leaks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    type_errors: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+struct Expected {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    #[serde(default)]
+    num_solutions: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    solutions: Vec<ExpectedSolution>,
+}
+
+fn expected_solution(recipe: &Recipe) -> ExpectedSolution {
+    ExpectedSolution {
+        edges: recipe
+            .edges
+            .iter()
+            .map(|(from, to)| format!("{} -> {}", from, to))
+            .collect(),
+        leaks: recipe
+            .feedback
+            .leaks
+            .iter()
+            .map(|Leak(_, node, expected, source, tag)| {
+                format!(
+                    "{}: expected '{}', found '{}' from {}",
+                    node, expected, tag, source
+                )
+            })
+            .collect(),
+        type_errors: recipe
+            .feedback
+            .type_errors
+            .iter()
+            .map(|TypeError(_, from, from_ty, to, to_ty)| {
+                format!(
+                    "{} -> {}: expected '{}', found '{}'",
+                    from, to, to_ty, from_ty
+                )
+            })
+            .collect(),
+    }
+}
+
+fn run_example(data: &str) -> Result<Expected, serde_json::Error> {
+    // Like ibis::run_ibis, but reports examples that do not parse rather than panicking.
+    let recipes: Ibis = serde_json::from_str(data)?;
+    let mut runtime = Ibis::new();
+    runtime.add_recipes(recipes);
+    let results = runtime.extract_solutions_with_loss(Some(0));
+    Ok(Expected {
+        warnings: results.shared.warnings.clone(),
+        num_solutions: results.num_solutions,
+        solutions: results.recipes.iter().map(expected_solution).collect(),
+    })
+}
+
+fn examples(dir: &Path) -> Vec<PathBuf> {
+    let mut examples: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("Should be able to list the examples")
+        .map(|entry| entry.expect("Should be able to read the examples").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    examples.sort();
+    examples
+}
+
+#[test]
+fn examples_match_their_expected_output() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let expected_dir = dir.join("expected");
+    let bless = std::env::var("BLESS").is_ok_and(|value| value == "1");
+    let mut failures = vec![];
+    for example in examples(&dir) {
+        let name = example.file_name().expect("Examples should have names");
+        let expected_path = expected_dir.join(name);
+        let data = std::fs::read_to_string(&example).expect("Should be able to read the example");
+        let actual = match run_example(&data) {
+            Ok(actual) => actual,
+            Err(error) => {
+                failures.push(format!("{} does not parse: {}", example.display(), error));
+                continue;
+            }
+        };
+        let actual_json = serde_json::to_string_pretty(&actual).expect("Should serialize") + "\n";
+        if bless {
+            std::fs::create_dir_all(&expected_dir).expect("Should create the expected directory");
+            std::fs::write(&expected_path, actual_json).expect("Should write the expected output");
+            continue;
+        }
+        match std::fs::read_to_string(&expected_path) {
+            Ok(expected_json) => {
+                let expected: Expected = serde_json::from_str(&expected_json)
+                    .unwrap_or_else(|e| panic!("Invalid {}: {}", expected_path.display(), e));
+                if expected != actual {
+                    failures.push(format!(
+                        "{} does not match {}:\n{}",
+                        example.display(),
+                        expected_path.display(),
+                        pretty_assertions::Comparison::new(&expected, &actual)
+                    ));
+                }
+            }
+            Err(_) => failures.push(format!(
+                "{} has no expected output, run with BLESS=1 to create {}",
+                example.display(),
+                expected_path.display()
+            )),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,152 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, HasFieldTag, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// A person is stored, and then read back by a directory (names only) and an auditor (everything).
+fn person_recipe(claims: &str, checks: &str) -> String {
+    person_recipe_with_reader("{name: String}", claims, checks)
+}
+
+fn person_recipe_with_reader(directory_ty: &str, claims: &str, checks: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "person", "write {{name: String, ssn: String}}"],
+        ["p_directory", "directory", "read {}"],
+        ["p_directory", "listing", "write List(String)"],
+        ["p_audit", "audit", "read {{name: String, ssn: String}}"]
+      ],
+      "edges": [["person", "directory"], ["person", "audit"]],
+      "claims": {},
+      "checks": {}
+    }}
+  ]
+}}"#,
+        directory_ty, claims, checks
+    )
+}
+
+fn feedback(data: &str) -> (Vec<String>, Vec<String>) {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut field_tags: Vec<String> = recipe
+        .feedback
+        .has_field_tags
+        .iter()
+        .map(|HasFieldTag(_, source, node, label, tag)| {
+            format!("{}.{} <- {} ({})", node, label, source, tag)
+        })
+        .collect();
+    field_tags.sort();
+    let mut leaks: Vec<String> = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, _, source, tag)| format!("{} <- {} ({})", node, source, tag))
+        .collect();
+    leaks.sort();
+    (field_tags, leaks)
+}
+
+#[test]
+fn field_tags_only_flow_to_readers_of_the_field() {
+    let data = person_recipe(r#"[["person.ssn", "private"]]"#, "[]");
+    assert_eq!(
+        feedback(&data),
+        (
+            vec![
+                "audit.ssn <- person (private)".to_string(),
+                "person.ssn <- person (private)".to_string(),
+            ],
+            vec![]
+        )
+    );
+}
+
+#[test]
+fn checks_on_a_node_include_its_fields() {
+    let data = person_recipe(
+        r#"[["person.ssn", "private"]]"#,
+        r#"[["directory", "public"], ["audit", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["audit <- person (private)".to_string()]
+    );
+}
+
+#[test]
+fn checks_on_a_field_include_tags_on_its_node() {
+    let data = person_recipe(
+        r#"[["person", "private"]]"#,
+        r#"[["audit.name", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["audit.name <- person (private)".to_string()]
+    );
+}
+
+#[test]
+fn checks_on_a_field_ignore_tags_on_other_fields() {
+    let data = person_recipe(
+        r#"[["person.ssn", "private"]]"#,
+        r#"[["audit.name", "public"], ["audit.ssn", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["audit.ssn <- person (private)".to_string()]
+    );
+}
+
+#[test]
+fn field_tags_taint_the_outputs_of_a_particle() {
+    let data = person_recipe(
+        r#"[["person.name", "private"]]"#,
+        r#"[["listing", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["listing <- person (private)".to_string()]
+    );
+}
+
+#[test]
+fn field_tags_reach_readers_of_any_type() {
+    let data = person_recipe_with_reader(
+        "*",
+        r#"[["person.ssn", "private"]]"#,
+        r#"[["directory", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["directory <- person (private)".to_string()]
+    );
+}
+
+#[test]
+fn field_tags_reach_readers_of_named_types() {
+    let data = person_recipe_with_reader(
+        "String",
+        r#"[["person.ssn", "private"]]"#,
+        r#"[["directory", "public"]]"#,
+    );
+    assert_eq!(
+        feedback(&data).1,
+        vec!["directory <- person (private)".to_string()]
+    );
+}
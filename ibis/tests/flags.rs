@@ -21,7 +21,7 @@ fn unknown_flag_generates_warning() {
     let warning = results
         .shared
         .warnings
-        .get(0)
+        .first()
         .expect("Should have a single value");
     let expected = r#"Unknown flag 'unknown_and_unexpected_flag' set to: true"#;
     assert!(
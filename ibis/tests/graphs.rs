@@ -31,7 +31,8 @@ fn create_combinations() {
   ]
 }"#,
         &|recipe| {
-            let mut in_nodes: Vec<String> = (&recipe.edges)
+            let mut in_nodes: Vec<String> = recipe
+                .edges
                 .iter()
                 .map(|(from, _to)| format!("{}", from.get_type()))
                 .collect();
@@ -39,7 +40,7 @@ fn create_combinations() {
             in_nodes.join("")
         },
     );
-    let expected: Vec<String> = vec!["", "a", "ab", "abc", "ac", "b", "bc", "c"]
+    let expected: Vec<String> = ["", "a", "ab", "abc", "ac", "b", "bc", "c"]
         .iter()
         .map(|s| s.to_string())
         .collect();
@@ -67,7 +68,7 @@ fn create_edges() {
   ]
 }"#,
     );
-    let expected: Vec<String> = vec!["", "a -> b", "a -> b, b -> a", "b -> a"]
+    let expected: Vec<String> = ["", "a -> b", "a -> b, b -> a", "b -> a"]
         .iter()
         .map(|s| s.to_string())
         .collect();
@@ -96,7 +97,7 @@ fn create_typed_edges() {
   ]
 }"#,
     );
-    let expected: Vec<String> = vec!["", "a -> b", "a -> b, b -> a", "b -> a"]
+    let expected: Vec<String> = ["", "a -> b", "a -> b, b -> a", "b -> a"]
         .iter()
         .map(|s| s.to_string())
         .collect();
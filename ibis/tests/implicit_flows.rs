@@ -0,0 +1,117 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, ImplicitFlow, Leak};
+use pretty_assertions::assert_eq;
+
+// A router reads a private key press and decides which of two panels to write a document into.
+fn router_recipe(extra: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_keyboard", "keyboard", "write UserAction(Key)"],
+        ["p_store", "document", "write Text"],
+        ["p_router", "key", "read UserAction(Key)"],
+        ["p_router", "doc", "read Text"],
+        ["p_router", "left", "write Text"],
+        ["p_router", "right", "write Text"],
+        ["p_panel", "panel", "read Text"]
+      ],
+      "edges": [["keyboard", "key"], ["document", "doc"], ["left", "panel"]],
+      "claims": [["keyboard", "private"]],
+      "checks": [["panel", "public"]],
+      {}
+    }}
+  ]
+}}"#,
+        extra
+    )
+}
+
+const ROUTES_DOCUMENT: &str =
+    r#""internal_flows": [["p_router", "doc", "left"], ["p_router", "doc", "right"]]"#;
+
+fn leaks_and_implicit_flows(data: &str) -> (Vec<String>, Vec<String>) {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut leaks: Vec<String> = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, _, source, tag)| format!("{} <- {} ({})", node, source, tag))
+        .collect();
+    leaks.sort();
+    let mut implicit_flows: Vec<String> = recipe
+        .feedback
+        .implicit_flows
+        .iter()
+        .map(|ImplicitFlow(_, node, _, source, tag)| format!("{} <- {} ({})", node, source, tag))
+        .collect();
+    implicit_flows.sort();
+    (leaks, implicit_flows)
+}
+
+#[test]
+fn control_flow_is_ignored_by_default() {
+    let data = router_recipe(ROUTES_DOCUMENT);
+    assert_eq!(leaks_and_implicit_flows(&data), (vec![], vec![]));
+}
+
+#[test]
+fn control_inputs_taint_all_outputs() {
+    let data = router_recipe(&format!(
+        r#"{}, "control_inputs": ["key"]"#,
+        ROUTES_DOCUMENT
+    ));
+    assert_eq!(
+        leaks_and_implicit_flows(&data),
+        (vec![], vec!["panel <- keyboard (private)".to_string()])
+    );
+}
+
+#[test]
+fn explicit_leaks_are_not_reported_as_implicit_flows() {
+    let data = router_recipe(r#""control_inputs": ["key"]"#);
+    assert_eq!(
+        leaks_and_implicit_flows(&data),
+        (vec!["panel <- keyboard (private)".to_string()], vec![])
+    );
+}
+
+#[test]
+fn control_inputs_can_be_declassified() {
+    let data = router_recipe(&format!(
+        r#"{}, "control_inputs": ["key"], "trusted_to_remove_tag": [["left", "private"]]"#,
+        ROUTES_DOCUMENT
+    ));
+    assert_eq!(leaks_and_implicit_flows(&data), (vec![], vec![]));
+}
+
+#[test]
+fn implicit_flows_prevent_solutions_when_planning() {
+    let planning = |data: String| -> usize {
+        let data = data.replace(r#""planning": false"#, r#""planning": true"#);
+        let results: Ibis = get_solutions(&data, None);
+        results.num_solutions
+    };
+    assert_eq!(planning(router_recipe(ROUTES_DOCUMENT)), 16);
+    assert_eq!(
+        planning(router_recipe(&format!(
+            r#"{}, "control_inputs": ["key"]"#,
+            ROUTES_DOCUMENT
+        ))),
+        0
+    );
+}
@@ -0,0 +1,136 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use ibis::{get_solutions, Ibis, IntegrityViolation};
+use pretty_assertions::assert_eq;
+use utils::all_edges;
+
+fn violations(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    let mut violations: Vec<String> = results
+        .recipes
+        .iter()
+        .flat_map(|recipe| recipe.feedback.integrity_violations.iter())
+        .map(|IntegrityViolation(_, node, required, source, tag)| {
+            format!("{} ({}) <- {} ({})", node, required, source, tag)
+        })
+        .collect();
+    violations.sort();
+    violations
+}
+
+const FORM_TO_SQL: &str = r#"
+  "capabilities": [["write", "read"]],
+  "less_trusted_than": [
+    ["untrusted", "validated"],
+    ["validated", "trusted"]
+  ],
+"#;
+
+#[test]
+fn untrusted_data_must_not_reach_a_trusted_sink() {
+    let data = format!(
+        r#"
+{{
+  {}
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "input", "write Query"],
+        ["p_db", "sql", "read Query"]
+      ],
+      "edges": [["input", "sql"]],
+      "integrity_claims": [["input", "untrusted"]],
+      "integrity_checks": [["sql", "trusted"]]
+    }}
+  ]
+}}"#,
+        FORM_TO_SQL
+    );
+    assert_eq!(
+        violations(&data),
+        vec!["sql (trusted) <- input (untrusted)"]
+    );
+}
+
+#[test]
+fn more_trusted_data_satisfies_less_demanding_checks() {
+    let data = format!(
+        r#"
+{{
+  {}
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "input", "write Query"],
+        ["p_db", "sql", "read Query"]
+      ],
+      "edges": [["input", "sql"]],
+      "integrity_claims": [["input", "trusted"]],
+      "integrity_checks": [["sql", "validated"]]
+    }}
+  ]
+}}"#,
+        FORM_TO_SQL
+    );
+    assert_eq!(violations(&data), Vec::<String>::new());
+}
+
+#[test]
+fn endorsement_removes_integrity_tags() {
+    let data = format!(
+        r#"
+{{
+  {}
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "input", "write Query"],
+        ["p_escape", "raw", "read Query"],
+        ["p_escape", "escaped", "write Query"],
+        ["p_db", "sql", "read Query"]
+      ],
+      "edges": [["input", "raw"], ["escaped", "sql"]],
+      "integrity_claims": [["input", "untrusted"]],
+      "integrity_checks": [["sql", "trusted"]],
+      "trusted_to_endorse": [["escaped", "untrusted"]]
+    }}
+  ]
+}}"#,
+        FORM_TO_SQL
+    );
+    assert_eq!(violations(&data), Vec::<String>::new());
+}
+
+#[test]
+fn planning_avoids_integrity_violations() {
+    let data = format!(
+        r#"
+{{
+  "flags": {{"planning": true}},
+  {}
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_form", "input", "write Query"],
+        ["p_escape", "raw", "read Query"],
+        ["p_escape", "escaped", "write Query"],
+        ["p_db", "sql", "read Query"]
+      ],
+      "integrity_claims": [["input", "untrusted"]],
+      "integrity_checks": [["sql", "trusted"]],
+      "trusted_to_endorse": [["escaped", "untrusted"]]
+    }}
+  ]
+}}"#,
+        FORM_TO_SQL
+    );
+    assert_eq!(
+        all_edges(&data),
+        vec!["escaped -> raw, escaped -> sql, input -> raw"]
+    );
+}
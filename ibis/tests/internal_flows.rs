@@ -0,0 +1,102 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// A particle that copies contacts and location to separate outputs.
+fn recipe(flows: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"dot": true}},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_contacts", "contacts", "write Data"],
+        ["p_location", "location", "write Data"],
+        ["p_split", "contacts_in", "read Data"],
+        ["p_split", "location_in", "read Data"],
+        ["p_split", "contacts_out", "write Data"],
+        ["p_split", "location_out", "write Data"],
+        ["p_share", "share", "read Data"]
+      ],
+      "edges": [
+        ["contacts", "contacts_in"],
+        ["location", "location_in"],
+        ["location_out", "share"]
+      ],
+      "claims": [["contacts", "private"]],
+      "checks": [["share", "public"]],
+      {}
+    }}
+  ]
+}}"#,
+        flows
+    )
+}
+
+fn leaks(results: &Ibis) -> Vec<String> {
+    let mut leaks: Vec<String> = results
+        .recipes
+        .iter()
+        .flat_map(|recipe| recipe.feedback.leaks.iter())
+        .map(|Leak(_, node, _, source, tag)| format!("{} <- {} ({})", node, source, tag))
+        .collect();
+    leaks.sort();
+    leaks
+}
+
+#[test]
+fn undeclared_particles_flow_from_every_input_to_every_output() {
+    let results = get_solutions(&recipe(r#""metadata": {}"#), None);
+    assert_eq!(leaks(&results), vec!["share <- contacts (private)"]);
+}
+
+#[test]
+fn declared_internal_flows_replace_the_default() {
+    let results = get_solutions(
+        &recipe(
+            r#""internal_flows": [
+        ["p_split", "contacts_in", "contacts_out"],
+        ["p_split", "location_in", "location_out"]
+      ]"#,
+        ),
+        None,
+    );
+    assert_eq!(leaks(&results), Vec::<String>::new());
+}
+
+#[test]
+fn declared_internal_flows_still_propagate_tags() {
+    let results = get_solutions(
+        &recipe(r#""internal_flows": [["p_split", "contacts_in", "location_out"]]"#),
+        None,
+    );
+    assert_eq!(leaks(&results), vec!["share <- contacts (private)"]);
+}
+
+#[test]
+fn particles_can_declare_no_internal_flow() {
+    let results = get_solutions(&recipe(r#""no_internal_flow": ["p_split"]"#), None);
+    assert_eq!(leaks(&results), Vec::<String>::new());
+}
+
+#[test]
+fn declared_internal_flows_are_shown_in_dot_output() {
+    let results = get_solutions(
+        &recipe(r#""internal_flows": [["p_split", "contacts_in", "contacts_out"]]"#),
+        None,
+    );
+    let dot = results.dot_output.expect("Should have dot output");
+    assert!(
+        dot.contains("_h_contacts_out[style=dashed color=gray];"),
+        "Missing internal flow in {}",
+        dot
+    );
+}
@@ -0,0 +1,259 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use ibis::{export_json_schema, import_json_schema, Ent};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use utils::all_edges;
+
+fn ty(name: &str) -> Ent {
+    Ent::from(name.to_string())
+}
+
+fn import(schema: serde_json::Value) -> String {
+    import_json_schema(&schema).ty.to_string()
+}
+
+#[test]
+fn import_primitive_schemas() {
+    assert_eq!(import(json!({"type": "string"})), "String");
+    assert_eq!(import(json!({"type": "integer"})), "Int");
+    assert_eq!(import(json!({"type": "number"})), "Number");
+    assert_eq!(import(json!({"type": "boolean"})), "Boolean");
+    assert_eq!(import(json!({"type": "null"})), "Null");
+    assert_eq!(import(json!({})), "*");
+    assert_eq!(import(json!({"type": ["string", "null"]})), "Null | String");
+}
+
+#[test]
+fn import_objects_with_required_and_optional_properties() {
+    assert_eq!(
+        import(json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer"},
+            },
+            "required": ["name"],
+        })),
+        "{age?: Int, name: String}"
+    );
+}
+
+#[test]
+fn import_enums_and_unions() {
+    assert_eq!(
+        import(json!({"enum": ["small", "large", 42, null]})),
+        "\"large\" | \"small\" | 42 | Null"
+    );
+    assert_eq!(import(json!({"const": true})), "true");
+    assert_eq!(
+        import(json!({"anyOf": [{"type": "string"}, {"type": "integer"}]})),
+        "Int | String"
+    );
+    assert_eq!(
+        import(
+            json!({"oneOf": [{"type": "array", "items": {"type": "string"}}, {"type": "null"}]})
+        ),
+        "List(String) | Null"
+    );
+}
+
+#[test]
+fn import_constraints_as_refinements() {
+    assert_eq!(
+        import(json!({"type": "integer", "minimum": 0, "exclusiveMaximum": 100})),
+        "Int where 0 <= x <= 99"
+    );
+    assert_eq!(
+        import(json!({"type": "string", "maxLength": 256, "pattern": "^[a-z]+$"})),
+        "String where len <= 256 and x matches /^[a-z]+$/"
+    );
+}
+
+#[test]
+fn import_definitions_as_subtypes() {
+    let imported = import_json_schema(&json!({
+        "type": "array",
+        "items": {"$ref": "#/$defs/Person"},
+        "$defs": {
+            "Person": {
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "required": ["name"],
+            },
+        },
+    }));
+    assert_eq!(imported.ty.to_string(), "List(Person)");
+    let subtypes: Vec<String> = imported
+        .subtypes
+        .iter()
+        .map(|subtype| format!("{} <: {}", subtype.0, subtype.1))
+        .collect();
+    assert_eq!(
+        subtypes,
+        vec!["Person <: name: String", "name: String <: Person"]
+    );
+}
+
+#[test]
+fn import_definitions_by_their_full_reference() {
+    let imported = import_json_schema(&json!({
+        "type": "object",
+        "properties": {
+            "a": {"$ref": "#/$defs/a/Item"},
+            "b": {"$ref": "#/definitions/Item"},
+        },
+        "required": ["a", "b"],
+        "$defs": {"a": {"Item": {"type": "string"}}},
+        "definitions": {"Item": {"type": "integer"}},
+    }));
+    assert_eq!(imported.ty.to_string(), "{a: a/Item, b: Item}");
+    let subtypes: Vec<String> = imported
+        .subtypes
+        .iter()
+        .map(|subtype| format!("{} <: {}", subtype.0, subtype.1))
+        .collect();
+    assert_eq!(
+        subtypes,
+        vec![
+            "a/Item <: String",
+            "String <: a/Item",
+            "Item <: Int",
+            "Int <: Item"
+        ]
+    );
+}
+
+#[test]
+fn unknown_references_are_reported() {
+    let imported = import_json_schema(&json!({"$ref": "#/$defs/Missing"}));
+    assert_eq!(imported.ty.to_string(), "*");
+    assert_eq!(
+        imported.warnings,
+        vec!["Unknown JSON Schema reference '#/$defs/Missing'".to_string()]
+    );
+}
+
+#[test]
+fn import_all_of_by_merging_its_members() {
+    assert_eq!(
+        import(json!({
+            "type": "object",
+            "allOf": [
+                {"properties": {"name": {"type": "string"}}, "required": ["name"]},
+                {"properties": {"age": {"type": "integer"}}},
+            ],
+        })),
+        "{age?: Int, name: String}"
+    );
+}
+
+#[test]
+fn imported_types_can_be_read_back() {
+    let imported = import_json_schema(&json!({
+        "type": "object",
+        "properties": {
+            "first name": {"type": "string"},
+            "a:b": {"enum": ["say \"hi\"", "back\\slash"]},
+            "removed": false,
+        },
+        "required": ["first name", "a:b"],
+    }));
+    assert_eq!(
+        imported.ty.to_string(),
+        "{a_b: \"back\\\\slash\" | \"say \\\"hi\\\"\", first_name: String}"
+    );
+    assert_eq!(ty(&imported.ty.to_string()), imported.ty);
+    assert_eq!(imported.warnings, Vec::<String>::new());
+    assert_eq!(
+        export_json_schema(&imported.ty)["properties"]["a_b"],
+        json!({"enum": ["back\\slash", "say \"hi\""]})
+    );
+}
+
+#[test]
+fn false_schemas_are_reported() {
+    let imported = import_json_schema(&json!(false));
+    assert_eq!(imported.ty.to_string(), "*");
+    assert_eq!(
+        imported.warnings,
+        vec!["JSON Schema 'false' has no values, so is imported as '*'".to_string()]
+    );
+}
+
+#[test]
+fn export_types_as_json_schema() {
+    assert_eq!(
+        export_json_schema(&ty(
+            "{name: String, age?: Int where x >= 0, tags: List(String)}"
+        )),
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string"}},
+            },
+            "required": ["name", "tags"],
+        })
+    );
+    assert_eq!(
+        export_json_schema(&ty("\"small\" | \"large\"")),
+        json!({"enum": ["large", "small"]})
+    );
+    assert_eq!(
+        export_json_schema(&ty("String | Null")),
+        json!({"anyOf": [{"type": "null"}, {"type": "string"}]})
+    );
+    assert_eq!(
+        export_json_schema(&ty("private String")),
+        json!({"type": "string", "x-ibis-capability": "private"})
+    );
+    assert_eq!(export_json_schema(&ty("Image")), json!({"title": "Image"}));
+}
+
+#[test]
+fn exported_schemas_round_trip() {
+    for name in [
+        "{id: Int where 1 <= x <= 10, name?: String where len <= 8}",
+        "List(\"a\" | \"b\") | Null",
+        "private {secret: String}",
+        "String where x matches /^a/ and x matches /b$/",
+    ] {
+        let exported = export_json_schema(&ty(name));
+        assert_eq!(
+            import_json_schema(&exported).ty,
+            ty(name),
+            "Round trip of {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn handles_can_reference_json_schemas() {
+    let data = json!({
+        "flags": {"planning": true},
+        "json_schemas": {
+            "Person": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "email": {"type": "string"},
+                },
+                "required": ["name"],
+            },
+        },
+        "nodes": [
+            ["p_a", "a", "{name: String, email: String}"],
+            ["p_b", "b", "Person"],
+            ["p_c", "c", "{email: String}"],
+        ],
+    });
+    assert_eq!(all_edges(&data.to_string()), vec!["a -> b, a -> c"]);
+}
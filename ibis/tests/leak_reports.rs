@@ -0,0 +1,132 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ent, Ibis, LeakReport};
+use pretty_assertions::assert_eq;
+
+// Two private sources reach a checked node, one of them directly and one through a copier.
+fn recipe(extra_edges: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_a", "a", "write Data"],
+        ["p_b", "b", "write Data"],
+        ["p_copy", "copy_in", "read Data"],
+        ["p_copy", "copy_out", "write Data"],
+        ["p_sink", "sink", "read Data"],
+        ["p_log", "log", "read Data"]
+      ],
+      "edges": [["a", "copy_in"], ["copy_out", "sink"], ["b", "sink"], ["a", "sink"]{}],
+      "claims": [["a", "private"], ["b", "private"]],
+      "checks": [["sink", "public"]]
+    }}
+  ]
+}}"#,
+        extra_edges
+    )
+}
+
+fn names(ents: &[Ent]) -> Vec<String> {
+    ents.iter().map(|ent| ent.to_string()).collect()
+}
+
+fn describe(report: &LeakReport) -> String {
+    let mut sources = names(&report.sources);
+    sources.sort();
+    let mut paths: Vec<String> = report
+        .paths
+        .iter()
+        .map(|path| names(path).join(" -> "))
+        .collect();
+    paths.sort();
+    format!(
+        "{} expected '{}' found {:?} from {:?} via {:?} in {} solution(s)",
+        report.node,
+        report.expected,
+        names(&report.tags),
+        sources,
+        paths,
+        report.num_solutions
+    )
+}
+
+#[test]
+fn leaks_are_aggregated_by_check() {
+    let results: Ibis = get_solutions(&recipe(""), None);
+    assert_eq!(results.recipes[0].feedback.leaks.len(), 2);
+    let reports: Vec<String> = results.leak_reports.iter().map(describe).collect();
+    assert_eq!(
+        reports,
+        vec![
+            "sink expected 'public' found [\"private\"] from [\"a\", \"b\"] via [\"a -> sink\", \"b -> sink\"] in 1 solution(s)"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn paths_are_minimal() {
+    let data = recipe("").replace(r#", ["a", "sink"]"#, "");
+    let results: Ibis = get_solutions(&data, None);
+    let reports: Vec<String> = results.leak_reports.iter().map(describe).collect();
+    assert_eq!(
+        reports,
+        vec![
+            "sink expected 'public' found [\"private\"] from [\"a\", \"b\"] via [\"a -> copy_in -> copy_out -> sink\", \"b -> sink\"] in 1 solution(s)"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn reports_are_deduplicated_across_solutions() {
+    let data = recipe("").replace(
+        "  ]\n}",
+        r#"  ,{
+      "edges": [["a", "copy_in"], ["copy_out", "sink"], ["b", "sink"], ["a", "sink"], ["b", "log"]]
+    }
+  ]
+}"#,
+    );
+    let results: Ibis = get_solutions(&data, None);
+    assert_eq!(results.recipes.len(), 2);
+    let reports: Vec<String> = results.leak_reports.iter().map(describe).collect();
+    assert_eq!(
+        reports,
+        vec![
+            "sink expected 'public' found [\"private\"] from [\"a\", \"b\"] via [\"a -> sink\", \"b -> sink\"] in 2 solution(s)"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn reports_are_grouped_by_check_across_solutions() {
+    let data = recipe("").replace(
+        "  ]\n}",
+        r#"  ,{
+      "edges": [["a", "copy_in"], ["copy_out", "sink"], ["b", "log"]]
+    }
+  ]
+}"#,
+    );
+    let results: Ibis = get_solutions(&data, None);
+    assert_eq!(results.recipes.len(), 2);
+    let reports: Vec<String> = results.leak_reports.iter().map(describe).collect();
+    assert_eq!(
+        reports,
+        vec![
+            "sink expected 'public' found [\"private\"] from [\"a\", \"b\"] via [\"a -> copy_in -> copy_out -> sink\", \"a -> sink\", \"b -> sink\"] in 2 solution(s)"
+                .to_string()
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis};
+use pretty_assertions::assert_eq;
+
+fn lint(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    results.shared.warnings
+}
+
+#[test]
+fn lint_is_off_by_default() {
+    let data = r#"
+{
+  "subtypes": [["A", "B"], ["B", "A"]],
+  "recipes": [{"nodes": [["p_a", "a", "Undeclared"]]}]
+}"#;
+    assert_eq!(lint(data), Vec::<String>::new());
+}
+
+#[test]
+fn lint_reports_equivalent_types() {
+    let data = r#"
+{
+  "flags": {"lint": true},
+  "subtypes": [["Text", "String"], ["String", "Text"], ["Name", "Text"]],
+  "recipes": [{"nodes": [["p_a", "a", "Name"]]}]
+}"#;
+    assert_eq!(
+        lint(data),
+        vec!["Types 'String', 'Text' are subtypes of each other, so they are equivalent"]
+    );
+}
+
+#[test]
+fn lint_reports_conflicting_generic_declarations() {
+    let data = r#"
+{
+  "flags": {"lint": true},
+  "subtypes": [
+    ["List", "ibis.GenericType"],
+    ["List", "ibis.InductiveType"],
+    ["Seq", "ibis.GenericType"],
+    ["List", "Seq"]
+  ],
+  "recipes": [{"nodes": [["p_a", "a", "List(Seq(*))"]]}]
+}"#;
+    assert_eq!(
+        lint(data),
+        vec!["Generic type 'List' (ibis.GenericType and ibis.InductiveType) is declared a subtype of 'Seq' (ibis.GenericType), so their arguments will not be related"]
+    );
+}
+
+#[test]
+fn lint_reports_unused_and_undeclared_types() {
+    let data = r#"
+{
+  "flags": {"lint": true},
+  "capabilities": [["write", "read"]],
+  "subtypes": [["Image", "Blob"], ["Audio", "Sound"], ["write", "read"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "write Image"],
+        ["p_b", "b", "read {data: Blob, meta?: Metadata}"]
+      ]
+    }
+  ]
+}"#;
+    assert_eq!(
+        lint(data),
+        vec![
+            "Type 'Audio' is declared but is not related to the type of any node",
+            "Type 'Sound' is declared but is not related to the type of any node",
+            "Type 'Metadata' is used by node 'b' but is never declared",
+        ]
+    );
+}
+
+#[test]
+fn lint_reports_similar_names() {
+    let data = r#"
+{
+  "flags": {"lint": true},
+  "subtypes": [["Imgae", "Blob"], ["Image", "Blob"], ["Int", "Number"], ["Inc", "Number"]],
+  "recipes": [{"nodes": [["p_a", "a", "Image"], ["p_b", "b", "Imgae"], ["p_c", "c", "Int | Inc"]]}]
+}"#;
+    assert_eq!(
+        lint(data),
+        vec!["Types 'Image' and 'Imgae' have similar names, is one a typo?"]
+    );
+}
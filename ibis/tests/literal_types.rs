@@ -0,0 +1,92 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use pretty_assertions::assert_eq;
+use utils::all_edges;
+
+#[test]
+fn literals_are_subtypes_of_their_base_types() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_small", "small", "any \"small\""],
+        ["p_answer", "answer", "any 42"],
+        ["p_yes", "yes", "any true"],
+        ["p_string", "string", "any String"],
+        ["p_int", "int", "any Int"],
+        ["p_boolean", "boolean", "any Boolean"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["answer -> int, small -> string, yes -> boolean".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn enumerations_accept_their_members() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_small", "small", "any \"small\""],
+        ["p_huge", "huge", "any \"huge\""],
+        ["p_size", "size", "any \"small\" | \"medium\" | \"large\""],
+        ["p_string", "string", "any String"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> =
+        vec!["huge -> string, size -> string, small -> size, small -> string".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn enumerations_can_be_used_as_fields() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_writer", "writer", "any {name: String, size: \"small\"}"],
+        ["p_reader", "reader", "any {name: String, size: \"small\" | \"large\"}"],
+        ["p_strict", "strict", "any {name: String, size: 1 | 2}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["writer -> reader".to_string()];
+    assert_eq!(solutions, expected);
+}
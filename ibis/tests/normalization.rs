@@ -0,0 +1,52 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::Ent;
+use pretty_assertions::assert_eq;
+
+fn ty(name: &str) -> Ent {
+    Ent::from(name.to_string())
+}
+
+#[test]
+fn product_fields_are_sorted() {
+    assert_eq!(ty("{b: Y, a: X}"), ty("{a: X, b: Y}"));
+    assert_eq!(ty("{b: Y, a?: X}").to_string(), "{a?: X, b: Y}");
+    assert_eq!(ty("{Mortal, Man}").to_string(), "{Man, Mortal}");
+}
+
+#[test]
+fn nested_products_and_unions_are_flattened() {
+    assert_eq!(ty("{a: X, {c: Z, b: Y}}").to_string(), "{a: X, b: Y, c: Z}");
+    assert_eq!(ty("A | (B | C)").to_string(), "A | B | C");
+    assert_eq!(ty("{{{a: X}}}").to_string(), "a: X");
+}
+
+#[test]
+fn duplicate_members_are_removed() {
+    assert_eq!(ty("A | B | A").to_string(), "A | B");
+    assert_eq!(ty("A | A"), ty("A"));
+    assert_eq!(ty("{a: X, a: X}"), ty("a: X"));
+}
+
+#[test]
+fn types_are_normalized_inside_other_types() {
+    assert_eq!(ty("List({b: Y, a: X})"), ty("List({a: X, b: Y})"));
+    assert_eq!(ty("read {b: Y, a: X}"), ty("read {a: X, b: Y}"));
+    assert_eq!(ty("c: ({b: Y, a: X})"), ty("c: {a: X, b: Y}"));
+}
+
+#[test]
+fn ordered_types_are_not_reordered() {
+    assert_eq!(ty("Map(V, K)").to_string(), "Map(V, K)");
+}
+
+#[test]
+fn union_members_are_sorted() {
+    assert_eq!(ty("B | A"), ty("A | B"));
+    assert_eq!(ty("B | A").to_string(), "A | B");
+    assert_eq!(ty("List(Null | String)"), ty("List(String | Null)"));
+}
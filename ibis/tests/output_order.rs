@@ -0,0 +1,123 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// The entities are interned in the reverse of their name order, so any output that follows the
+// interning order (or crepe's hashing) will not be sorted.
+const DATA: &str = r#"
+{
+  "flags": {"planning": true},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["ibis.Public", "ibis.Private"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_z", "z_out", "write Int"],
+        ["p_y", "y_out", "write Int"],
+        ["p_b", "b_in", "read Int"],
+        ["p_a", "a_in", "read Int"]
+      ],
+      "claims": [["z_out", "ibis.Private"], ["y_out", "ibis.Private"]],
+      "checks": [["b_in", "ibis.Public"], ["a_in", "ibis.Public"]]
+    }
+  ]
+}"#;
+
+fn edge_names(recipe: &ibis::Recipe) -> Vec<String> {
+    recipe
+        .edges
+        .iter()
+        .map(|(from, to)| format!("{} -> {}", from, to))
+        .collect()
+}
+
+#[test]
+fn recipes_and_their_edges_are_ordered_by_name() {
+    let results: Ibis = get_solutions(DATA, None);
+    let recipes: Vec<Vec<String>> = results.recipes.iter().map(edge_names).collect();
+    let mut sorted = recipes.clone();
+    sorted.sort();
+    assert_eq!(recipes, sorted);
+    for edges in &recipes {
+        let mut sorted_edges = edges.clone();
+        sorted_edges.sort();
+        assert_eq!(edges, &sorted_edges);
+    }
+}
+
+#[test]
+fn feedback_is_ordered_by_name() {
+    let data = DATA.replace(
+        r#""planning": true"#,
+        r#""planning": false"#,
+    )
+    .replace(
+        r#""checks""#,
+        r#""edges": [["z_out", "b_in"], ["z_out", "a_in"], ["y_out", "b_in"], ["y_out", "a_in"]],
+      "checks""#,
+    );
+    let results: Ibis = get_solutions(&data, None);
+    let recipe = results.recipes.first().expect("Should have a recipe");
+    assert_eq!(
+        edge_names(recipe),
+        vec![
+            "y_out -> a_in",
+            "y_out -> b_in",
+            "z_out -> a_in",
+            "z_out -> b_in"
+        ]
+    );
+    let leaks: Vec<String> = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, _, source, _)| format!("{} <- {}", node, source))
+        .collect();
+    assert_eq!(
+        leaks,
+        vec![
+            "a_in <- y_out",
+            "a_in <- z_out",
+            "b_in <- y_out",
+            "b_in <- z_out"
+        ]
+    );
+}
+
+#[test]
+fn serialized_output_is_stable() {
+    let first = serde_json::to_string(&get_solutions(DATA, None)).unwrap();
+    let second = serde_json::to_string(&get_solutions(DATA, None)).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn recipes_are_ordered_by_their_edges_rather_than_their_feedback() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["ibis.Public", "ibis.Private"]],
+  "recipes": [
+    {
+      "nodes": [["p_z", "z_out", "write Int"], ["p_b", "b_in", "read Int"]],
+      "claims": [["z_out", "ibis.Private"]],
+      "checks": [["b_in", "ibis.Public"]],
+      "edges": [["z_out", "b_in"]]
+    },
+    {
+      "nodes": [["p_y", "y_out", "write Int"], ["p_a", "a_in", "read Int"]],
+      "edges": [["y_out", "a_in"]]
+    }
+  ]
+}"#;
+    let results: Ibis = get_solutions(data, None);
+    let recipes: Vec<Vec<String>> = results.recipes.iter().map(edge_names).collect();
+    assert_eq!(recipes, vec![vec!["y_out -> a_in"], vec!["z_out -> b_in"]]);
+    assert_eq!(results.recipes[1].feedback.leaks.len(), 1);
+}
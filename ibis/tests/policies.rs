@@ -0,0 +1,163 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, Leak};
+use pretty_assertions::assert_eq;
+
+// Consented preferences and a location are both sent to an uploader.
+fn upload_recipe(policy: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "consented"], ["public", "location"]],
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_prefs", "prefs", "write Data"],
+        ["p_gps", "position", "write Data"],
+        ["p_upload", "upload", "read Data"]
+      ],
+      "edges": [["prefs", "upload"], ["position", "upload"]],
+      "claims": [["prefs", "consented"], ["position", "location"]],
+      "policies": [["upload", "{}"]]
+    }}
+  ]
+}}"#,
+        policy
+    )
+}
+
+fn leaks(data: &str) -> (Vec<String>, Vec<String>) {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut leaks: Vec<String> = recipe
+        .feedback
+        .leaks
+        .iter()
+        .map(|Leak(_, node, policy, source, tag)| {
+            format!("{} <- {} ({}), expected '{}'", node, source, tag, policy)
+        })
+        .collect();
+    leaks.sort();
+    (leaks, results.shared.warnings)
+}
+
+#[test]
+fn disjunctions_allow_either_tag() {
+    assert_eq!(
+        leaks(&upload_recipe("public OR consented")),
+        (
+            vec![
+                "upload <- position (location), expected 'ibis.Policy(public OR consented)'"
+                    .to_string()
+            ],
+            vec![]
+        )
+    );
+    assert_eq!(
+        leaks(&upload_recipe("consented OR location")),
+        (vec![], vec![])
+    );
+}
+
+#[test]
+fn negations_forbid_a_tag() {
+    assert_eq!(
+        leaks(&upload_recipe("NOT location")),
+        (
+            vec!["upload <- position (location), expected 'ibis.Policy(NOT location)'".to_string()],
+            vec![]
+        )
+    );
+}
+
+#[test]
+fn negations_are_the_complement_of_tags() {
+    let data = |policy: &str| {
+        upload_recipe(policy)
+            .replace(
+                r#"[["public", "consented"], ["public", "location"]]"#,
+                r#"[["public", "area"], ["area", "location"], ["location", "address"]]"#,
+            )
+            .replace(
+                r#"["p_upload", "upload", "read Data"]"#,
+                r#"["p_upload", "upload", "read Data"],
+        ["p_map", "area", "write Data"],
+        ["p_book", "address", "write Data"]"#,
+            )
+            .replace(
+                r#"["position", "upload"]"#,
+                r#"["position", "upload"], ["area", "upload"], ["address", "upload"]"#,
+            )
+            .replace(
+                r#"["position", "location"]"#,
+                r#"["position", "location"], ["area", "area"], ["address", "address"]"#,
+            )
+    };
+    assert_eq!(
+        leaks(&data("NOT location")).0,
+        vec![
+            "upload <- area (area), expected 'ibis.Policy(NOT location)'".to_string(),
+            "upload <- position (location), expected 'ibis.Policy(NOT location)'".to_string(),
+        ]
+    );
+    // Every tag is either within a tag or not, so no tag satisfies both.
+    assert_eq!(leaks(&data("consented AND NOT consented")).0.len(), 4);
+    assert_eq!(
+        leaks(&data("location OR NOT location")).0,
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn conjunctions_require_both() {
+    assert_eq!(
+        leaks(&upload_recipe("consented AND NOT location")).0,
+        vec![
+            "upload <- position (location), expected 'ibis.Policy(consented AND NOT location)'"
+                .to_string()
+        ]
+    );
+    assert_eq!(
+        leaks(&upload_recipe("NOT (consented OR location)")).0,
+        vec![
+            "upload <- position (location), expected 'ibis.Policy(NOT (consented OR location))'"
+                .to_string(),
+            "upload <- prefs (consented), expected 'ibis.Policy(NOT (consented OR location))'"
+                .to_string(),
+        ]
+    );
+}
+
+#[test]
+fn quantified_policies_restrict_sources() {
+    assert_eq!(
+        leaks(&upload_recipe("no tag from p_gps")).0,
+        vec![
+            "upload <- position (location), expected 'ibis.Policy(no tag from p_gps)'".to_string()
+        ]
+    );
+    assert_eq!(
+        leaks(&upload_recipe("tag from p_prefs OR NOT NOT location")).0,
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn malformed_policies_are_reported() {
+    assert_eq!(
+        leaks(&upload_recipe("public OR")),
+        (
+            vec![],
+            vec!["Could not parse policy 'public OR' on node 'upload'".to_string()]
+        )
+    );
+}
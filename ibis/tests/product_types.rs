@@ -152,3 +152,111 @@ fn product_of_products() {
     ];
     assert_eq!(solutions, expected);
 }
+
+#[test]
+fn missing_optional_fields_are_allowed() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_named", "named", "any {name: String}"],
+        ["p_aged", "aged", "any {name: String, age: Int}"],
+        ["p_mistyped", "mistyped", "any {name: String, age: String}"],
+        ["p_reader", "reader", "any {name: String, age?: Int}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec![
+        "aged -> named, aged -> reader, mistyped -> named, named -> reader, reader -> named"
+            .to_string(),
+    ];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn optional_fields_are_not_required_fields() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "any {name: String, age?: Int}"],
+        ["p_b", "b", "any {name: String, age: Int}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["b -> a".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn optional_fields_support_depth_subtyping() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "subtypes": [
+    ["Int", "Number"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_int", "int", "any {name: String, age?: Int}"],
+        ["p_number", "number", "any {name: String, age?: Number}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["int -> number".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn missing_optional_fields_are_allowed_in_nested_fields() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_writer", "writer", "any {id: Int, person: {name: String}}"],
+        ["p_reader", "reader", "any {id: Int, person: {name: String, age?: Int}}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["reader -> writer, writer -> reader".to_string()];
+    assert_eq!(solutions, expected);
+}
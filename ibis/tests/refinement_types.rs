@@ -0,0 +1,117 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use pretty_assertions::assert_eq;
+use utils::all_edges;
+
+#[test]
+fn narrower_refinements_are_subtypes_of_wider_refinements() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_small", "small", "any Int where x < 10"],
+        ["p_percent", "percent", "any Int where 0 <= x < 100"],
+        ["p_large", "large", "any Int where x < 100"],
+        ["p_int", "int", "any Int"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec![
+        "large -> int, percent -> int, percent -> large, small -> int, small -> large".to_string(),
+    ];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn refinements_are_checked_on_fields() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_short", "short", "any {name: String where len <= 64}"],
+        ["p_sanitised", "sanitised", "any {name: String where len <= 256}"],
+        ["p_raw", "raw", "any {name: String}"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> =
+        vec!["sanitised -> raw, short -> raw, short -> sanitised".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn patterns_are_compared_syntactically() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_id", "id", "any String where len <= 8 and x matches /[a-z]+/"],
+        ["p_lower", "lower", "any String where x matches /[a-z]+/"],
+        ["p_other", "other", "any String where x matches /[a-z]*/"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["id -> lower".to_string()];
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn literals_are_subtypes_of_refinements_they_satisfy() {
+    let solutions = all_edges(
+        r#"
+{
+  "flags": {
+    "planning": true
+  },
+  "capabilities": [
+    ["any", "any"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_five", "five", "any 5"],
+        ["p_hundred", "hundred", "any 100"],
+        ["p_percent", "percent", "any Int where 0 <= x < 100"]
+      ]
+    }
+  ]
+}"#,
+    );
+    let expected: Vec<String> = vec!["five -> percent".to_string()];
+    assert_eq!(solutions, expected);
+}
@@ -0,0 +1,157 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis};
+use pretty_assertions::assert_eq;
+
+fn suggestions(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    results
+        .suggest_repairs(recipe)
+        .iter()
+        .map(|suggestion| format!("{} (fixes {})", suggestion.repair, suggestion.fixes))
+        .collect()
+}
+
+#[test]
+fn suggest_declassifiers_for_leaks() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "face"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_camera", "frame", "write Image"],
+        ["p_blur", "blur_in", "read Image"],
+        ["p_blur", "blur_out", "write Image"],
+        ["p_display", "display", "read Image"],
+        ["p_cloud", "upload", "read Image"]
+      ],
+      "edges": [["frame", "display"], ["frame", "upload"]],
+      "claims": [["frame", "face"]],
+      "checks": [["upload", "public"]],
+      "trusted_to_remove_tag": [["blur_out", "face"]]
+    }
+  ]
+}"#;
+    assert_eq!(
+        suggestions(data),
+        vec![
+            "Remove the edge frame -> upload (fixes 1)".to_string(),
+            "Replace the edge frame -> upload with frame -> blur_in and blur_out -> upload (fixes 1)"
+                .to_string(),
+        ]
+    );
+}
+
+#[test]
+fn suggest_subtypes_for_type_errors() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_count", "count", "write Int"],
+        ["p_chart", "values", "read Number"],
+        ["p_chart", "labels", "read String"]
+      ],
+      "edges": [["count", "values"], ["count", "labels"]]
+    }
+  ]
+}"#;
+    assert_eq!(
+        suggestions(data),
+        vec![
+            "Declare 'Int' a subtype of 'Number' (fixes 1)".to_string(),
+            "Declare 'Int' a subtype of 'String' (fixes 1)".to_string(),
+            "Remove the edge count -> labels (fixes 1)".to_string(),
+            "Remove the edge count -> values (fixes 1)".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn recipes_without_violations_need_no_repairs() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "recipes": [
+    {
+      "nodes": [["p_a", "a", "write Int"], ["p_b", "b", "read Int"]],
+      "edges": [["a", "b"]]
+    }
+  ]
+}"#;
+    assert_eq!(suggestions(data), Vec::<String>::new());
+}
+
+#[test]
+fn suggest_repairs_for_integrity_violations() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "less_trusted_than": [["untrusted", "trusted"]],
+  "recipes": [
+    {
+      "nodes": [["p_form", "input", "write Query"], ["p_db", "sql", "read Query"]],
+      "edges": [["input", "sql"]],
+      "integrity_claims": [["input", "untrusted"]],
+      "integrity_checks": [["sql", "trusted"]]
+    }
+  ]
+}"#;
+    assert_eq!(
+        suggestions(data),
+        vec!["Remove the edge input -> sql (fixes 1)".to_string()]
+    );
+}
+
+#[test]
+fn suggest_declassifiers_for_implicit_flows() {
+    let data = r#"
+{
+  "flags": {"planning": false},
+  "capabilities": [["write", "read"]],
+  "less_private_than": [["public", "private"]],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_keyboard", "keyboard", "write UserAction(Key)"],
+        ["p_router", "key", "read UserAction(Key)"],
+        ["p_router", "left", "write Text"],
+        ["p_mask", "mask_in", "read Text"],
+        ["p_mask", "mask_out", "write Text"],
+        ["p_panel", "panel", "read Text"]
+      ],
+      "edges": [["keyboard", "key"], ["left", "panel"]],
+      "no_internal_flow": ["p_router"],
+      "control_inputs": ["key"],
+      "claims": [["keyboard", "private"]],
+      "checks": [["panel", "public"]],
+      "trusted_to_remove_tag": [["mask_out", "private"]]
+    }
+  ]
+}"#;
+    assert_eq!(
+        suggestions(data),
+        vec![
+            "Remove the edge keyboard -> key (fixes 1)".to_string(),
+            "Remove the edge left -> panel (fixes 1)".to_string(),
+            "Replace the edge left -> panel with left -> mask_in and mask_out -> panel (fixes 1)"
+                .to_string(),
+        ]
+    );
+}
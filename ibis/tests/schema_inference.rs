@@ -0,0 +1,88 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{infer_schema, infer_type, Ent};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[test]
+fn infer_primitive_types() {
+    assert_eq!(infer_schema(r#""text""#), "String");
+    assert_eq!(infer_schema("42"), "Int");
+    assert_eq!(infer_schema("4.2"), "Number");
+    assert_eq!(infer_schema("true"), "Boolean");
+    assert_eq!(infer_schema("null"), "Null");
+}
+
+#[test]
+fn infer_a_product_type_from_an_object() {
+    assert_eq!(
+        infer_schema(r#"{"name": "Ada", "age": 36}"#),
+        "{age: Int, name: String}"
+    );
+}
+
+#[test]
+fn infer_a_labelled_type_from_an_object_with_a_single_field() {
+    assert_eq!(infer_schema(r#"{"name": "Ada"}"#), "name: String");
+}
+
+#[test]
+fn infer_lists_of_unions_from_heterogeneous_arrays() {
+    assert_eq!(infer_schema(r#"[1, "two", 3]"#), "List(Int | String)");
+    assert_eq!(infer_schema(r#"[1, 2.5]"#), "List(Number)");
+    assert_eq!(infer_schema("[]"), "List(*)");
+}
+
+#[test]
+fn infer_optional_fields_from_multiple_samples() {
+    assert_eq!(
+        infer_schema(
+            r#"
+{"name": "Ada", "age": 36}
+{"name": "Grace", "email": "grace@example.com"}
+"#
+        ),
+        "{age?: Int, email?: String, name: String}"
+    );
+}
+
+#[test]
+fn infer_nested_types() {
+    let samples = vec![json!({
+        "people": [
+            {"name": "Ada", "languages": ["Analytical Engine"]},
+            {"name": "Grace", "languages": ["COBOL", "FLOW-MATIC"], "rank": "Rear Admiral"}
+        ]
+    })];
+    let ty = infer_type(&samples);
+    assert_eq!(
+        ty.to_string(),
+        "people: List({languages: List(String), name: String, rank?: String})"
+    );
+    // The inferred types can be used like any other type.
+    assert_eq!(Ent::from(ty.to_string()), ty);
+}
+
+#[test]
+fn keys_are_made_into_labels_that_can_be_read_back() {
+    let ty = infer_type(&[json!({
+        "first name": "Ada",
+        "a:b": 1,
+        "{x, y}": 2,
+        "maybe?": true,
+        "a|b": 3,
+        "\"quoted\"": 4,
+        "42": 5,
+        "true": 6,
+        "": 7,
+    })]);
+    assert_eq!(
+        ty.to_string(),
+        "{_: Int, _42: Int, _quoted_: Int, _true: Int, _x__y_: Int, a_b: Int, first_name: String, maybe_: Boolean}"
+    );
+    assert_eq!(Ent::from(ty.to_string()), ty);
+}
@@ -0,0 +1,181 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, Leak, PrivacyLabel};
+use pretty_assertions::assert_eq;
+
+const LEVELS: &str = r#"
+  "less_private_than": [
+    ["public", "internal"],
+    ["internal", "confidential"],
+    ["confidential", "secret"]
+  ],"#;
+
+fn leaks(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    let mut leaks: Vec<String> = results
+        .recipes
+        .iter()
+        .flat_map(|recipe| recipe.feedback.leaks.iter())
+        .map(|Leak(_, node, allowed, source, tag)| {
+            format!("{} ({}) <- {} ({})", node, allowed, source, tag)
+        })
+        .collect();
+    leaks.sort();
+    leaks
+}
+
+fn labels(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    let mut labels: Vec<String> = results
+        .recipes
+        .iter()
+        .flat_map(|recipe| recipe.feedback.privacy_labels.iter())
+        .map(|PrivacyLabel(_, node, label)| format!("{}: {}", node, label))
+        .collect();
+    labels.sort();
+    labels
+}
+
+fn recipe(checks: &str, claims: &str) -> String {
+    format!(
+        r#"
+{{
+  {}
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_a", "a", "Data"],
+        ["p_b", "b", "Data"]
+      ],
+      "edges": [["a", "b"]],
+      "claims": {},
+      "checks": {}
+    }}
+  ]
+}}"#,
+        LEVELS, claims, checks
+    )
+}
+
+#[test]
+fn the_privacy_ordering_is_transitive() {
+    let data = recipe(r#"[["b", "internal"]]"#, r#"[["a", "secret"]]"#);
+    assert_eq!(leaks(&data), vec!["b (internal) <- a (secret)"]);
+}
+
+#[test]
+fn less_private_data_can_flow_to_more_private_levels() {
+    let data = recipe(r#"[["b", "confidential"]]"#, r#"[["a", "internal"]]"#);
+    assert_eq!(leaks(&data), Vec::<String>::new());
+    let data = recipe(r#"[["b", "confidential"]]"#, r#"[["a", "confidential"]]"#);
+    assert_eq!(leaks(&data), Vec::<String>::new());
+}
+
+#[test]
+fn public_and_private_are_the_bottom_and_top_of_the_lattice() {
+    let data = recipe(r#"[["b", "ibis.Public"]]"#, r#"[["a", "internal"]]"#);
+    assert_eq!(leaks(&data), vec!["b (ibis.Public) <- a (internal)"]);
+    let data = recipe(r#"[["b", "ibis.Private"]]"#, r#"[["a", "secret"]]"#);
+    assert_eq!(leaks(&data), Vec::<String>::new());
+}
+
+#[test]
+fn unordered_tags_are_independent() {
+    let data = r#"
+{
+  "less_private_than": [
+    ["public", "private"],
+    ["not_downstream", "downstream"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "Data"],
+        ["p_b", "b", "Data"]
+      ],
+      "edges": [["a", "b"]],
+      "claims": [["a", "downstream"]],
+      "checks": [["b", "public"]]
+    }
+  ]
+}"#;
+    assert_eq!(leaks(data), Vec::<String>::new());
+}
+
+#[test]
+fn nodes_are_labelled_with_the_join_of_their_tags() {
+    let data = r#"
+{
+  "less_private_than": [
+    ["public", "location"],
+    ["public", "contacts"],
+    ["location", "personal"],
+    ["contacts", "personal"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "Data"],
+        ["p_b", "b", "Data"],
+        ["p_c", "c", "Data"]
+      ],
+      "edges": [["a", "c"], ["b", "c"]],
+      "claims": [["a", "location"], ["b", "contacts"]]
+    }
+  ]
+}"#;
+    assert_eq!(
+        labels(data),
+        vec!["a: location", "b: contacts", "c: personal"]
+    );
+}
+
+#[test]
+fn claimed_tags_are_more_private_than_public() {
+    let data = r#"
+{
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "Data"],
+        ["p_b", "b", "Data"]
+      ],
+      "edges": [["a", "b"]],
+      "claims": [["a", "pii"]],
+      "checks": [["b", "ibis.Public"]]
+    }
+  ]
+}"#;
+    assert_eq!(leaks(data), vec!["b (ibis.Public) <- a (pii)"]);
+}
+
+#[test]
+fn checks_compare_the_label_of_the_node() {
+    // Neither tag is more private than the other, so their join (personal) is not 'location'.
+    let data = r#"
+{
+  "less_private_than": [
+    ["public", "location"],
+    ["public", "contacts"],
+    ["location", "personal"],
+    ["contacts", "personal"]
+  ],
+  "recipes": [
+    {
+      "nodes": [
+        ["p_a", "a", "Data"],
+        ["p_b", "b", "Data"],
+        ["p_c", "c", "Data"]
+      ],
+      "edges": [["a", "c"], ["b", "c"]],
+      "claims": [["a", "location"], ["b", "contacts"]],
+      "checks": [["c", "location"]]
+    }
+  ]
+}"#;
+    assert_eq!(leaks(data), vec!["c (location) <- b (contacts)"]);
+}
@@ -0,0 +1,98 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, CategoryLeak, Ibis};
+use pretty_assertions::assert_eq;
+
+// A private contact list is shown on screen, uploaded and saved to a file.
+fn contacts_recipe(categories: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "subtypes": [["LocalFile", "File"]],
+  {},
+  "recipes": [
+    {{
+      "nodes": [
+        ["p_contacts", "contacts", "write List(Contact)"],
+        ["p_screen", "screen", "read List(Contact)"],
+        ["p_upload", "upload", "read List(Contact)"],
+        ["p_upload", "upload_status", "write Status"],
+        ["p_save", "save", "read LocalFile"]
+      ],
+      "edges": [["contacts", "screen"], ["contacts", "upload"], ["contacts", "save"]],
+      "claims": [["contacts", "ibis.Private"]]
+    }}
+  ]
+}}"#,
+        categories
+    )
+}
+
+fn category_leaks(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    let recipe = results
+        .recipes
+        .first()
+        .expect("Should have a single recipe");
+    let mut leaks: Vec<String> = recipe
+        .feedback
+        .category_leaks
+        .iter()
+        .map(|CategoryLeak(_, category, node, expected, source, tag)| {
+            format!(
+                "{}: {} <- {} ({}), expected '{}'",
+                category, node, source, tag, expected
+            )
+        })
+        .collect();
+    leaks.sort();
+    leaks
+}
+
+#[test]
+fn particle_categories_apply_to_their_inputs() {
+    let data = contacts_recipe(
+        r#""particle_categories": [["p_upload", "network"]],
+  "category_checks": [["network", "ibis.Public"]]"#,
+    );
+    assert_eq!(
+        category_leaks(&data),
+        vec!["network: upload <- contacts (ibis.Private), expected 'ibis.Public'".to_string()]
+    );
+}
+
+#[test]
+fn type_categories_apply_to_subtypes() {
+    let data = contacts_recipe(
+        r#""type_categories": [["File", "disk"]],
+  "category_checks": [["disk", "ibis.Public"]]"#,
+    );
+    assert_eq!(
+        category_leaks(&data),
+        vec!["disk: save <- contacts (ibis.Private), expected 'ibis.Public'".to_string()]
+    );
+}
+
+#[test]
+fn policies_apply_to_categories() {
+    let data = contacts_recipe(
+        r#""particle_categories": [["p_upload", "network"], ["p_screen", "display"]],
+  "type_categories": [["File", "disk"]],
+  "category_policies": [["network", "NOT ibis.Private"], ["disk", "NOT ibis.Private"]]"#,
+    );
+    assert_eq!(
+        category_leaks(&data),
+        vec![
+            "disk: save <- contacts (ibis.Private), expected 'ibis.Policy(NOT ibis.Private)'"
+                .to_string(),
+            "network: upload <- contacts (ibis.Private), expected 'ibis.Policy(NOT ibis.Private)'"
+                .to_string(),
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+use ibis::{get_solutions, Ibis, Sol};
+use pretty_assertions::assert_eq;
+
+fn recipe(edges: &str) -> String {
+    format!(
+        r#"
+{{
+  "flags": {{"planning": false}},
+  "capabilities": [["write", "read"]],
+  "recipes": [
+    {{
+      "nodes": [["p_a", "a", "write Int"], ["p_b", "b", "read Int"], ["p_c", "c", "read Int"]],
+      "edges": {}
+    }}
+  ]
+}}"#,
+        edges
+    )
+}
+
+fn ids(data: &str) -> Vec<String> {
+    let results: Ibis = get_solutions(data, None);
+    results
+        .recipes
+        .iter()
+        .map(|recipe| String::from(recipe.id.expect("Solutions should have ids")))
+        .collect()
+}
+
+#[test]
+fn solution_ids_are_derived_from_edges() {
+    // The id of a solution does not depend on what was solved before it.
+    let before = ids(&recipe(r#"[["a", "b"]]"#));
+    ids(&recipe(r#"[["a", "c"], ["a", "b"]]"#));
+    assert_eq!(ids(&recipe(r#"[["a", "b"]]"#)), before);
+    assert_eq!(before, vec!["ab40d7820d408076"]);
+}
+
+#[test]
+fn solution_ids_do_not_depend_on_edge_order() {
+    assert_eq!(
+        ids(&recipe(r#"[["a", "b"], ["a", "c"]]"#)),
+        ids(&recipe(r#"[["a", "c"], ["a", "b"]]"#))
+    );
+}
+
+#[test]
+fn solution_ids_are_serialized() {
+    let results: Ibis = get_solutions(&recipe(r#"[["a", "b"]]"#), None);
+    let serialized = serde_json::to_value(&results).expect("Should serialize");
+    assert_eq!(serialized["recipes"][0]["id"], "ab40d7820d408076");
+    let sol: Sol =
+        serde_json::from_value(serialized["recipes"][0]["id"].clone()).expect("Should deserialize");
+    assert_eq!(Some(sol), results.recipes[0].id);
+}
+
+#[test]
+fn solution_ids_are_not_read_back_in() {
+    let results: Ibis = get_solutions(&recipe(r#"[["a", "b"]]"#), None);
+    let mut serialized = serde_json::to_value(&results).expect("Should serialize");
+    serialized["recipes"][0]["id"] = "0123456789abcdef".into();
+    let deserialized: Ibis = serde_json::from_value(serialized).expect("Should deserialize");
+    assert_eq!(deserialized.recipes[0].id, None);
+
+    // Output that is fed back in is re-solved with the same ids.
+    let again: Ibis = get_solutions(&serde_json::to_string(&deserialized).unwrap(), None);
+    assert_eq!(again.recipes[0].id, results.recipes[0].id);
+}
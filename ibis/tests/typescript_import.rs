@@ -0,0 +1,215 @@
+// Copyright 2022 Google LLC
+//
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file or at
+// https://developers.google.com/open-source/licenses/bsd
+
+mod utils;
+use ibis::{import_typescript, TypeScriptImport};
+use pretty_assertions::assert_eq;
+use utils::all_edges;
+
+fn aliases(imported: &TypeScriptImport) -> Vec<String> {
+    imported
+        .aliases
+        .iter()
+        .map(|(name, body)| format!("{} = {}", name, body))
+        .collect()
+}
+
+fn subtypes(imported: &TypeScriptImport) -> Vec<String> {
+    imported
+        .to_config()
+        .subtypes
+        .iter()
+        .map(|subtype| format!("{} <: {}", subtype.0, subtype.1))
+        .collect()
+}
+
+#[test]
+fn import_interfaces_with_optional_fields() {
+    let imported = import_typescript(
+        r#"
+// A person.
+export interface Person {
+  name: string;
+  readonly age?: number;
+  /* Tags are free-form. */
+  tags: string[];
+}
+"#,
+    );
+    assert_eq!(
+        aliases(&imported),
+        vec!["Person = {age?: number, name: string, tags: List(string)}"]
+    );
+    assert_eq!(imported.warnings, Vec::<String>::new());
+}
+
+#[test]
+fn import_type_aliases_with_unions_and_literals() {
+    let imported = import_typescript(
+        r#"
+type Size = 'small' | "large" | 42;
+declare type MaybeName =
+  | string
+  | null;
+"#,
+    );
+    assert_eq!(
+        aliases(&imported),
+        vec![
+            "Size = \"large\" | \"small\" | 42",
+            "MaybeName = null | string"
+        ]
+    );
+}
+
+#[test]
+fn import_generic_declarations() {
+    let imported = import_typescript(
+        r#"
+interface Box<T> { value: T; label: string }
+type Pair<A, B extends object = {}> = { first: A, second: B };
+type Names = Array<string>;
+type Lookup = Map<string, Box<number>>;
+"#,
+    );
+    assert_eq!(
+        aliases(&imported),
+        vec![
+            "Box(*) = {label: string, value: *}",
+            "Pair(*, *) = {first: *, second: *}",
+            "Names = List(string)",
+            "Lookup = Map(string, Box(number))",
+        ]
+    );
+    assert!(subtypes(&imported).contains(&"Box <: ibis.GenericType".to_string()));
+    assert!(subtypes(&imported).contains(&"Box <: ibis.InductiveType".to_string()));
+    assert!(subtypes(&imported).contains(&"Pair <: ibis.GenericType".to_string()));
+}
+
+#[test]
+fn import_interface_inheritance() {
+    let imported = import_typescript(
+        r#"
+interface Named { name: string }
+interface Marker {}
+interface Person extends Named, Marker { age: number }
+"#,
+    );
+    assert_eq!(
+        subtypes(&imported),
+        vec![
+            "Named <: name: string",
+            "name: string <: Named",
+            "Person <: Named",
+            "Person <: Marker",
+            "Person <: age: number",
+        ]
+    );
+}
+
+#[test]
+fn skip_unsupported_statements_with_a_warning() {
+    let imported = import_typescript(
+        r#"
+import { Thing } from './thing';
+declare function make(): Thing;
+declare namespace Things { const count: number; }
+type Id = string;
+"#,
+    );
+    assert_eq!(aliases(&imported), vec!["Id = string"]);
+    assert_eq!(
+        imported.warnings,
+        vec![
+            "Skipped unsupported TypeScript: 'import { Thing } from './thing';'",
+            "Skipped unsupported TypeScript: 'declare function make(): Thing;'",
+            "Skipped unsupported TypeScript: 'declare namespace Things { const count: number; }'",
+        ]
+    );
+}
+
+#[test]
+fn imported_types_drive_the_checks() {
+    let imported = import_typescript(
+        r#"
+interface Person { name: string; age?: number }
+"#,
+    );
+    let mut data = serde_json::to_value(imported.to_config()).expect("Couldn't serialize config");
+    data["nodes"] = serde_json::json!([
+        ["p_a", "a", "{name: string}"],
+        ["p_b", "b", "Person"],
+        ["p_c", "c", "{name: string, age: number}"],
+    ]);
+    data["flags"] = serde_json::json!({"planning": true});
+    let solutions = all_edges(&data.to_string());
+    assert_eq!(solutions, vec!["b -> a, c -> a, c -> b"]);
+}
+
+#[test]
+fn records_with_own_fields_are_not_inherited_interfaces() {
+    let imported = import_typescript(
+        r#"
+interface Named { name: string }
+interface Person extends Named { age: number }
+"#,
+    );
+    let mut data = serde_json::to_value(imported.to_config()).expect("Couldn't serialize config");
+    data["nodes"] = serde_json::json!([
+        ["p_a", "a", "{age: number}"],
+        ["p_b", "b", "Named"],
+        ["p_c", "c", "Person"],
+    ]);
+    data["flags"] = serde_json::json!({"planning": true});
+    let solutions = all_edges(&data.to_string());
+    assert_eq!(solutions, vec!["c -> a, c -> b"]);
+}
+
+#[test]
+fn imported_generics_are_related_by_their_arguments() {
+    let imported = import_typescript(
+        r#"
+interface Box<T> { value: T }
+"#,
+    );
+    let mut data = serde_json::to_value(imported.to_config()).expect("Couldn't serialize config");
+    data["nodes"] = serde_json::json!([["p_a", "a", "Box(number)"], ["p_b", "b", "Box(*)"],]);
+    data["flags"] = serde_json::json!({"planning": true});
+    let solutions = all_edges(&data.to_string());
+    assert_eq!(solutions, vec!["a -> b"]);
+}
+
+#[test]
+fn the_command_line_tool_outputs_aliases() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let mut child = Command::new(env!("CARGO_BIN_EXE_typescript"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Couldn't run the typescript tool");
+    child
+        .stdin
+        .take()
+        .expect("Should have stdin")
+        .write_all(b"type Id = string;\ninterface Person { name: string; id: Id }\n")
+        .expect("Couldn't write to the typescript tool");
+    let output = child.wait_with_output().expect("Typescript tool failed");
+    let config: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be JSON");
+    assert_eq!(
+        config,
+        serde_json::json!({
+            "subtypes": [
+                ["Id", "string"],
+                ["string", "Id"],
+                ["Person", "{id: Id, name: string}"],
+                ["{id: Id, name: string}", "Person"],
+            ]
+        })
+    );
+}
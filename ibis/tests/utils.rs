@@ -12,16 +12,13 @@ fn map_solutions_with_edge_loss<U>(
     on_result: &dyn Fn(&Recipe) -> U,
 ) -> Vec<U> {
     let recipes: Ibis = get_solutions(data, loss);
-    recipes
-        .recipes
-        .iter()
-        .map(|recipe| on_result(recipe))
-        .collect()
+    recipes.recipes.iter().map(on_result).collect()
 }
 
 fn solutions_with_edge_loss(data: &str, loss: Option<usize>) -> Vec<String> {
     let mut edges = map_solutions_with_edge_loss(data, loss, &|recipe: &Recipe| {
-        let mut in_nodes: Vec<String> = (&recipe.edges)
+        let mut in_nodes: Vec<String> = recipe
+            .edges
             .iter()
             .map(|(from, to)| format!("{} -> {}", from, to))
             .collect();